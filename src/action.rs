@@ -1,7 +1,9 @@
+use core::hash::{Hash, Hasher};
+
 use memuse::DynamicUsage;
 
 use crate::{
-    note::{ExtractedNoteCommitment, Nullifier, TransmittedNoteCiphertext},
+    note::{ExtractedNoteCommitment, Nullifier, PrunedNoteCiphertext, TransmittedNoteCiphertext},
     primitives::redpallas::{self, SpendAuth},
     value::ValueCommitment,
 };
@@ -105,6 +107,120 @@ impl<T> Action<T> {
     }
 }
 
+/// An [`Action`] whose encrypted note has been pruned of its memo.
+///
+/// Created from an action by [`Bundle::prune`](crate::Bundle::prune).
+#[derive(Debug, Clone)]
+pub struct PrunedAction<A> {
+    nf: Nullifier,
+    rk: redpallas::VerificationKey<SpendAuth>,
+    cmx: ExtractedNoteCommitment,
+    encrypted_note: PrunedNoteCiphertext,
+    cv_net: ValueCommitment,
+    authorization: A,
+}
+
+impl<A> PrunedAction<A> {
+    /// Constructs a `PrunedAction` from its constituent parts.
+    pub fn from_parts(
+        nf: Nullifier,
+        rk: redpallas::VerificationKey<SpendAuth>,
+        cmx: ExtractedNoteCommitment,
+        encrypted_note: PrunedNoteCiphertext,
+        cv_net: ValueCommitment,
+        authorization: A,
+    ) -> Self {
+        PrunedAction {
+            nf,
+            rk,
+            cmx,
+            encrypted_note,
+            cv_net,
+            authorization,
+        }
+    }
+
+    /// Returns the nullifier of the note being spent.
+    pub fn nullifier(&self) -> &Nullifier {
+        &self.nf
+    }
+
+    /// Returns the randomized verification key for the note being spent.
+    pub fn rk(&self) -> &redpallas::VerificationKey<SpendAuth> {
+        &self.rk
+    }
+
+    /// Returns the commitment to the new note being created.
+    pub fn cmx(&self) -> &ExtractedNoteCommitment {
+        &self.cmx
+    }
+
+    /// Returns the encrypted note ciphertext, without its memo.
+    pub fn encrypted_note(&self) -> &PrunedNoteCiphertext {
+        &self.encrypted_note
+    }
+
+    /// Returns the commitment to the net value created or consumed by this action.
+    pub fn cv_net(&self) -> &ValueCommitment {
+        &self.cv_net
+    }
+
+    /// Returns the authorization for this action.
+    pub fn authorization(&self) -> &A {
+        &self.authorization
+    }
+}
+
+impl<A> From<Action<A>> for PrunedAction<A> {
+    fn from(action: Action<A>) -> Self {
+        PrunedAction {
+            nf: action.nf,
+            rk: action.rk,
+            cmx: action.cmx,
+            encrypted_note: action.encrypted_note.prune(),
+            cv_net: action.cv_net,
+            authorization: action.authorization,
+        }
+    }
+}
+
+impl Action<redpallas::Signature<SpendAuth>> {
+    /// Passes each field of the canonical encoding of this action to `f`, in the order
+    /// of the v5 transaction format (with the spend authorization signature last).
+    fn encode_canonical(&self, mut f: impl FnMut(&[u8])) {
+        f(&self.cv_net.to_bytes());
+        f(&self.nf.to_bytes());
+        f(&<[u8; 32]>::from(&self.rk));
+        f(&self.cmx.to_bytes());
+        f(&self.encrypted_note.epk_bytes);
+        f(&self.encrypted_note.enc_ciphertext);
+        f(&self.encrypted_note.out_ciphertext);
+        f(&<[u8; 64]>::from(&self.authorization));
+    }
+
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.encode_canonical(|field| bytes.extend_from_slice(field));
+        bytes
+    }
+}
+
+/// Authorized actions are equal if their canonical encodings are equal.
+impl PartialEq for Action<redpallas::Signature<SpendAuth>> {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_canonical_bytes() == other.to_canonical_bytes()
+    }
+}
+
+impl Eq for Action<redpallas::Signature<SpendAuth>> {}
+
+impl Hash for Action<redpallas::Signature<SpendAuth>> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Every field has a fixed length, so the encoding is unambiguous.
+        self.encode_canonical(|field| state.write(field));
+    }
+}
+
 impl DynamicUsage for Action<redpallas::Signature<SpendAuth>> {
     #[inline(always)]
     fn dynamic_usage(&self) -> usize {
@@ -0,0 +1,518 @@
+//! A command-line tool for inspecting Orchard keys and bundles.
+//!
+//! ```text
+//! orchard-cli keys --seed <HEX> [--coin-type <N>] [--account <N>] [--index <N>]...
+//!                   [--show-spending-key]
+//! orchard-cli decode [<HEX>]
+//! orchard-cli decrypt [--fvk <HEX>]... [--ivk <HEX>]... [--ovk <HEX>]... [<HEX>]
+//! ```
+//!
+//! `keys` derives the spending key for a ZIP 32 account from a seed, and prints its
+//! viewing keys and its addresses at the given diversifier indices in both scopes. The
+//! spending key itself is only printed with `--show-spending-key`.
+//!
+//! `decode` prints the effecting data of a bundle, which is given in its [ZIP 225]
+//! encoding within a v5 transaction, starting at `nActionsOrchard`. `decrypt` performs
+//! trial decryption of the outputs of such a bundle with the given viewing keys. For
+//! both, the bundle is read from standard input if it is not given as an argument.
+//!
+//! All output is JSON, with byte strings encoded as hex.
+//!
+//! [ZIP 225]: https://zips.z.cash/zip-0225
+
+use std::fmt;
+use std::io::{self, Read};
+use std::process;
+
+use orchard::{
+    bundle::EffectsOnly,
+    keys::{FullViewingKey, IncomingViewingKey, OutgoingViewingKey, Scope, SpendingKey},
+    note::ExtractedNoteCommitment,
+    Address, Bundle, Note,
+};
+use serde_json::{json, Value};
+
+const USAGE: &str = "\
+Usage:
+    orchard-cli keys --seed <HEX> [--coin-type <N>] [--account <N>] [--index <N>]...
+                     [--show-spending-key]
+    orchard-cli decode [<HEX>]
+    orchard-cli decrypt [--fvk <HEX>]... [--ivk <HEX>]... [--ovk <HEX>]... [<HEX>]";
+
+/// The coin type of Zcash mainnet, as registered in SLIP 44.
+const MAINNET_COIN_TYPE: u32 = 133;
+
+/// An error that makes the tool exit without output.
+#[derive(Debug)]
+enum Error {
+    Usage,
+    Invalid(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Usage => f.write_str(USAGE),
+            Error::Invalid(msg) => f.write_str(msg),
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::Invalid(msg.into())
+}
+
+/// The options, flags and positional arguments of a command.
+#[derive(Debug)]
+struct Args {
+    options: Vec<(String, String)>,
+    flags: Vec<String>,
+    positional: Vec<String>,
+}
+
+impl Args {
+    /// Parses `args`, given the names of the options (which take a value) and flags
+    /// (which do not) that the command accepts.
+    fn parse(args: &[String], known_options: &[&str], known_flags: &[&str]) -> Result<Self, Error> {
+        let mut options = vec![];
+        let mut flags = vec![];
+        let mut positional = vec![];
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if let Some(name) = arg.strip_prefix("--") {
+                if known_flags.contains(&name) {
+                    flags.push(name.to_owned());
+                } else if known_options.contains(&name) {
+                    let value = iter.next().ok_or(Error::Usage)?;
+                    options.push((name.to_owned(), value.clone()));
+                } else {
+                    return Err(Error::Usage);
+                }
+            } else {
+                positional.push(arg.clone());
+            }
+        }
+        Ok(Args {
+            options,
+            flags,
+            positional,
+        })
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|n| n == name)
+    }
+
+    fn all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.options
+            .iter()
+            .filter(move |(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn one(&self, name: &str) -> Result<Option<&str>, Error> {
+        let mut values = self.all(name);
+        match (values.next(), values.next()) {
+            (value, None) => Ok(value),
+            _ => Err(invalid(format!("--{} may only be given once", name))),
+        }
+    }
+
+    fn one_u32(&self, name: &str, default: u32) -> Result<u32, Error> {
+        self.one(name)?.map_or(Ok(default), |v| {
+            v.parse()
+                .map_err(|_| invalid(format!("Invalid --{}: {}", name, v)))
+        })
+    }
+}
+
+fn parse_hex(what: &str, s: &str) -> Result<Vec<u8>, Error> {
+    hex::decode(s.trim()).map_err(|_| invalid(format!("Invalid hex for {}", what)))
+}
+
+fn parse_array<const N: usize>(what: &str, s: &str) -> Result<[u8; N], Error> {
+    parse_hex(what, s)?
+        .try_into()
+        .map_err(|_| invalid(format!("A {} must be {} bytes", what, N)))
+}
+
+/// Reads a bundle from `arg`, or from standard input if `arg` is `None`.
+fn read_bundle(arg: Option<&String>) -> Result<Bundle<EffectsOnly, i64>, Error> {
+    let encoded = match arg {
+        Some(arg) => parse_hex("bundle", arg)?,
+        None => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            parse_hex("bundle", &input)?
+        }
+    };
+
+    let mut reader = &encoded[..];
+    Bundle::read_effects(&mut reader)
+        .map_err(|e| invalid(format!("Invalid bundle: {}", e)))?
+        .ok_or_else(|| invalid("The bundle has no actions"))
+}
+
+fn scope_name(scope: Scope) -> &'static str {
+    match scope {
+        Scope::External => "external",
+        Scope::Internal => "internal",
+    }
+}
+
+fn address_json(address: &Address) -> Value {
+    json!(hex::encode(address.to_raw_address_bytes()))
+}
+
+fn note_json(note: &Note, address: &Address, memo: &[u8; 512]) -> Value {
+    json!({
+        "recipient": address_json(address),
+        "value": note.value().inner(),
+        "rho": hex::encode(note.rho().to_bytes()),
+        "rseed": hex::encode(note.rseed().as_bytes()),
+        "cmx": hex::encode(ExtractedNoteCommitment::from(note.commitment()).to_bytes()),
+        "memo": hex::encode(&memo[..]),
+    })
+}
+
+fn keys(args: &Args) -> Result<Value, Error> {
+    let seed = parse_hex("seed", args.one("seed")?.ok_or(Error::Usage)?)?;
+    let coin_type = args.one_u32("coin-type", MAINNET_COIN_TYPE)?;
+    let account = args.one_u32("account", 0)?;
+    let indices = args
+        .all("index")
+        .map(|j| {
+            j.parse::<u64>()
+                .map_err(|_| invalid(format!("Invalid --index: {}", j)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let sk = SpendingKey::from_zip32_seed(&seed, coin_type, account)
+        .map_err(|e| invalid(format!("Cannot derive spending key: {:?}", e)))?;
+
+    Ok(keys_json(
+        &sk,
+        coin_type,
+        account,
+        &indices,
+        args.flag("show-spending-key"),
+    ))
+}
+
+/// Describes the keys of `sk`, and its addresses at the given diversifier indices.
+fn keys_json(
+    sk: &SpendingKey,
+    coin_type: u32,
+    account: u32,
+    indices: &[u64],
+    show_spending_key: bool,
+) -> Value {
+    let fvk = FullViewingKey::from(sk);
+
+    let scopes = [Scope::External, Scope::Internal]
+        .iter()
+        .map(|&scope| {
+            let addresses = indices
+                .iter()
+                .map(|&j| {
+                    json!({
+                        "index": j,
+                        "address": address_json(&fvk.address_at(j, scope)),
+                    })
+                })
+                .collect::<Vec<_>>();
+            (
+                scope_name(scope).to_owned(),
+                json!({
+                    "ivk": hex::encode(fvk.to_ivk(scope).to_bytes()),
+                    "ovk": hex::encode(fvk.to_ovk(scope).as_ref()),
+                    "addresses": addresses,
+                }),
+            )
+        })
+        .collect::<serde_json::Map<_, _>>();
+
+    let mut output = json!({
+        "coin_type": coin_type,
+        "account": account,
+        "fvk": hex::encode(fvk.to_bytes()),
+        "scopes": scopes,
+    });
+    if show_spending_key {
+        output["sk"] = json!(hex::encode(sk.to_bytes()));
+    }
+    output
+}
+
+fn decode(args: &Args) -> Result<Value, Error> {
+    Ok(decode_json(&read_bundle(args.positional.first())?))
+}
+
+/// Describes the effecting data of `bundle`.
+fn decode_json(bundle: &Bundle<EffectsOnly, i64>) -> Value {
+    let actions = bundle
+        .actions()
+        .iter()
+        .map(|action| {
+            json!({
+                "cv_net": hex::encode(action.cv_net().to_bytes()),
+                "nullifier": hex::encode(action.nullifier().to_bytes()),
+                "rk": hex::encode(<[u8; 32]>::from(action.rk())),
+                "cmx": hex::encode(action.cmx().to_bytes()),
+                "epk": hex::encode(action.encrypted_note().epk_bytes),
+                "enc_ciphertext": hex::encode(&action.encrypted_note().enc_ciphertext[..]),
+                "out_ciphertext": hex::encode(&action.encrypted_note().out_ciphertext[..]),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "actions": actions,
+        "flags": {
+            "spends_enabled": bundle.flags().spends_enabled(),
+            "outputs_enabled": bundle.flags().outputs_enabled(),
+        },
+        "value_balance": bundle.value_balance(),
+        "anchor": hex::encode(bundle.anchor().to_bytes()),
+        "txid_digest": hex::encode(<[u8; 32]>::from(bundle.commitment())),
+    })
+}
+
+fn decrypt(args: &Args) -> Result<Value, Error> {
+    let mut ivks = vec![];
+    let mut ovks = vec![];
+    for fvk in args.all("fvk") {
+        let fvk = FullViewingKey::from_bytes(&parse_array("full viewing key", fvk)?)
+            .ok_or_else(|| invalid("Invalid full viewing key"))?;
+        for scope in [Scope::External, Scope::Internal] {
+            ivks.push(fvk.to_ivk(scope));
+            ovks.push(fvk.to_ovk(scope));
+        }
+    }
+    for ivk in args.all("ivk") {
+        let ivk = IncomingViewingKey::from_bytes(&parse_array("incoming viewing key", ivk)?);
+        ivks.push(Option::from(ivk).ok_or_else(|| invalid("Invalid incoming viewing key"))?);
+    }
+    for ovk in args.all("ovk") {
+        ovks.push(OutgoingViewingKey::from(parse_array::<32>(
+            "outgoing viewing key",
+            ovk,
+        )?));
+    }
+    if ivks.is_empty() && ovks.is_empty() {
+        return Err(invalid("No viewing keys were given"));
+    }
+
+    let bundle = read_bundle(args.positional.first())?;
+    Ok(decrypt_json(&bundle, &ivks, &ovks))
+}
+
+/// Describes the outputs of `bundle` that can be decrypted with the given viewing keys.
+fn decrypt_json(
+    bundle: &Bundle<EffectsOnly, i64>,
+    ivks: &[IncomingViewingKey],
+    ovks: &[OutgoingViewingKey],
+) -> Value {
+    let received = bundle
+        .decrypt_outputs_with_keys(ivks)
+        .iter()
+        .map(|(idx, ivk, note, address, memo)| {
+            json!({
+                "index": idx,
+                "ivk": hex::encode(ivk.to_bytes()),
+                "note": note_json(note, address, memo),
+            })
+        })
+        .collect::<Vec<_>>();
+    let recovered = bundle
+        .recover_outputs_with_ovks(ovks)
+        .iter()
+        .map(|(idx, ovk, note, address, memo)| {
+            json!({
+                "index": idx,
+                "ovk": hex::encode(ovk.as_ref()),
+                "note": note_json(note, address, memo),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "received": received,
+        "recovered": recovered,
+    })
+}
+
+fn run(args: &[String]) -> Result<Value, Error> {
+    match args.split_first() {
+        Some((cmd, rest)) if cmd == "keys" => keys(&Args::parse(
+            rest,
+            &["seed", "coin-type", "account", "index"],
+            &["show-spending-key"],
+        )?),
+        Some((cmd, rest)) if cmd == "decode" => {
+            let args = Args::parse(rest, &[], &[])?;
+            if args.positional.len() > 1 {
+                return Err(Error::Usage);
+            }
+            decode(&args)
+        }
+        Some((cmd, rest)) if cmd == "decrypt" => {
+            let args = Args::parse(rest, &["fvk", "ivk", "ovk"], &[])?;
+            if args.positional.len() > 1 {
+                return Err(Error::Usage);
+            }
+            decrypt(&args)
+        }
+        _ => Err(Error::Usage),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(output) => println!("{:#}", output),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(if matches!(e, Error::Usage) { 2 } else { 1 });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use orchard::{
+        builder::Builder,
+        bundle::{EffectsOnly, Flags},
+        keys::{FullViewingKey, Scope, SpendingKey},
+        tree::MerkleHashOrchard,
+        value::NoteValue,
+        Bundle,
+    };
+    use rand::rngs::OsRng;
+
+    use super::{decode_json, decrypt_json, keys_json, run, Args, Error};
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_args() {
+        let parsed = Args::parse(
+            &args(&["--index", "1", "--show", "x", "--index", "2"]),
+            &["index"],
+            &["show"],
+        )
+        .unwrap();
+        assert_eq!(parsed.all("index").collect::<Vec<_>>(), ["1", "2"]);
+        assert!(parsed.flag("show"));
+        assert_eq!(parsed.positional, ["x"]);
+        assert!(matches!(parsed.one("index"), Err(Error::Invalid(_))));
+
+        // Unknown options, and options without values, are usage errors.
+        assert!(matches!(
+            Args::parse(&args(&["--other", "1"]), &["index"], &[]),
+            Err(Error::Usage)
+        ));
+        assert!(matches!(
+            Args::parse(&args(&["--index"]), &["index"], &[]),
+            Err(Error::Usage)
+        ));
+
+        assert!(matches!(run(&args(&[])), Err(Error::Usage)));
+        assert!(matches!(run(&args(&["keys"])), Err(Error::Usage)));
+        assert!(matches!(
+            run(&args(&["keys", "--seed", "00", "--account", "x"])),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            run(&args(&["decode", "00", "00"])),
+            Err(Error::Usage)
+        ));
+        assert!(matches!(
+            run(&args(&["decrypt", "00"])),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn keys_output() {
+        let sk = SpendingKey::from_zip32_seed(&[7; 32], 133, 0).unwrap();
+        let fvk = FullViewingKey::from(&sk);
+
+        let output = keys_json(&sk, 133, 0, &[0, 5], false);
+        assert!(output.get("sk").is_none());
+        assert_eq!(output["fvk"], hex::encode(fvk.to_bytes()));
+        assert_eq!(
+            output["scopes"]["internal"]["addresses"][1]["address"],
+            hex::encode(fvk.address_at(5u32, Scope::Internal).to_raw_address_bytes())
+        );
+
+        let output = keys_json(&sk, 133, 0, &[], true);
+        assert_eq!(output["sk"], hex::encode(sk.to_bytes()));
+
+        // The command line only shows the spending key when asked to.
+        let seed = hex::encode([7; 32]);
+        let output = run(&args(&["keys", "--seed", &seed])).unwrap();
+        assert!(output.get("sk").is_none());
+        let output = run(&args(&["keys", "--seed", &seed, "--show-spending-key"])).unwrap();
+        assert_eq!(output["sk"], hex::encode(sk.to_bytes()));
+    }
+
+    #[test]
+    fn decode_and_decrypt_output() {
+        let sk = SpendingKey::from_bytes([0; 32]).unwrap();
+        let fvk = FullViewingKey::from(&sk);
+        let recipient = fvk.address_at(0u32, Scope::External);
+        let ovk = fvk.to_ovk(Scope::External);
+
+        let anchor = MerkleHashOrchard::empty_root(32.into()).into();
+        let mut builder = Builder::new(Flags::from_parts(false, true), anchor);
+        let memo = [0x42; 512];
+        builder
+            .add_recipient(
+                Some(ovk.clone()),
+                recipient,
+                NoteValue::from_raw(5000),
+                Some(memo),
+            )
+            .unwrap();
+        let bundle: Bundle<EffectsOnly, i64> = builder.build(OsRng).unwrap().into_effects_only();
+
+        let decoded = decode_json(&bundle);
+        assert_eq!(decoded["actions"].as_array().unwrap().len(), 2);
+        assert_eq!(decoded["flags"]["spends_enabled"], false);
+        assert_eq!(decoded["value_balance"], -5000);
+        assert_eq!(
+            decoded["txid_digest"],
+            hex::encode(<[u8; 32]>::from(bundle.commitment()))
+        );
+
+        let decrypted = decrypt_json(&bundle, &[fvk.to_ivk(Scope::External)], &[ovk]);
+        for (outputs, key) in [("received", "ivk"), ("recovered", "ovk")] {
+            let outputs = decrypted[outputs].as_array().unwrap();
+            assert_eq!(outputs.len(), 1);
+            assert!(outputs[0].get(key).is_some());
+            let note = &outputs[0]["note"];
+            assert_eq!(note["value"], 5000);
+            assert_eq!(
+                note["recipient"],
+                hex::encode(recipient.to_raw_address_bytes())
+            );
+            assert_eq!(note["memo"], hex::encode(&memo[..]));
+        }
+
+        // Keys that did not take part find nothing.
+        let other = FullViewingKey::from(&SpendingKey::from_bytes([1; 32]).unwrap());
+        let decrypted = decrypt_json(&bundle, &[other.to_ivk(Scope::External)], &[]);
+        assert!(decrypted["received"].as_array().unwrap().is_empty());
+    }
+}
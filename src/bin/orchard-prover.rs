@@ -0,0 +1,92 @@
+//! A service that creates proofs for Orchard bundles on behalf of other processes.
+//!
+//! The proving key is built once at startup. Requests are then read from standard input
+//! and answered on standard output, or, with `--socket <PATH>`, accepted from clients
+//! connecting to a Unix socket at `PATH`. Connections are served one at a time. See
+//! [`orchard::prover`] for the protocol.
+
+use std::io::{self, Read, Write};
+use std::process;
+
+use orchard::{circuit::ProvingKey, prover};
+
+const USAGE: &str = "Usage: orchard-prover [--socket <PATH>]";
+
+/// Standard input and output, as a single stream.
+struct Stdio {
+    stdin: io::Stdin,
+    stdout: io::Stdout,
+}
+
+impl Read for Stdio {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stdin.read(buf)
+    }
+}
+
+impl Write for Stdio {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stdout.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stdout.flush()
+    }
+}
+
+#[cfg(unix)]
+fn serve_socket(pk: &ProvingKey, path: &str) -> io::Result<()> {
+    use std::os::unix::net::UnixListener;
+
+    let listener = UnixListener::bind(path)?;
+    eprintln!("Listening on {}", path);
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = prover::serve(pk, stream) {
+                    eprintln!("Dropping connection: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Failed to accept connection: {}", e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn serve_socket(_: &ProvingKey, _: &str) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "Unix sockets are not supported on this platform",
+    ))
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let socket = match &args[..] {
+        [] => None,
+        [flag, path] if flag == "--socket" => Some(path.clone()),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    eprintln!("Building proving key...");
+    let pk = ProvingKey::build();
+
+    let result = match socket {
+        Some(path) => serve_socket(&pk, &path).map_err(prover::Error::from),
+        None => prover::serve(
+            &pk,
+            Stdio {
+                stdin: io::stdin(),
+                stdout: io::stdout(),
+            },
+        ),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
@@ -2,17 +2,21 @@
 
 use core::fmt;
 use core::iter;
+#[cfg(feature = "zsa")]
+use std::collections::BTreeMap;
 
 use ff::Field;
 use nonempty::NonEmpty;
 use pasta_curves::pallas;
 use rand::{prelude::SliceRandom, CryptoRng, RngCore};
+use zeroize::Zeroize;
 
 use crate::{
     action::Action,
     address::Address,
     bundle::{Authorization, Authorized, Bundle, Flags},
-    circuit::{Circuit, Instance, Proof, ProvingKey},
+    circuit::{Circuit, Instance, Proof, ProvingKey, ProvingRequest, VerifyingKey, WitnessFailure},
+    fees::FeeRule,
     keys::{
         FullViewingKey, OutgoingViewingKey, Scope, SpendAuthorizingKey, SpendValidatingKey,
         SpendingKey,
@@ -20,11 +24,18 @@ use crate::{
     note::{Note, TransmittedNoteCiphertext},
     note_encryption::OrchardNoteEncryption,
     primitives::redpallas::{self, Binding, SpendAuth},
+    prover::{self, Prover},
+    spec::SecretField,
     tree::{Anchor, MerklePath},
     value::{self, NoteValue, OverflowError, ValueCommitTrapdoor, ValueCommitment, ValueSum},
 };
 
-const MIN_ACTIONS: usize = 2;
+#[cfg(feature = "zsa")]
+use crate::note::AssetBase;
+
+pub mod selection;
+
+pub(crate) const MIN_ACTIONS: usize = 2;
 
 /// An error type for the kinds of errors that can occur during bundle construction.
 #[derive(Debug)]
@@ -41,6 +52,17 @@ pub enum Error {
     /// A signature is valid for more than one input. This should never happen if `alpha`
     /// is sampled correctly, and indicates a critical failure in randomness generation.
     DuplicateSignature,
+    /// An error occurred while proving a bundle with a [`Prover`].
+    ///
+    /// [`Prover`]: crate::prover::Prover
+    Prover(prover::Error),
+    /// The spends and outputs of a non-native asset do not have equal total value.
+    #[cfg(feature = "zsa")]
+    UnbalancedAsset(AssetBase),
+    /// A proof was requested for a bundle that transfers a non-native asset. Such
+    /// bundles require the OrchardZSA circuit, which is not implemented by this crate.
+    #[cfg(feature = "zsa")]
+    NonNativeAssetProof,
 }
 
 impl From<halo2_proofs::plonk::Error> for Error {
@@ -49,6 +71,12 @@ impl From<halo2_proofs::plonk::Error> for Error {
     }
 }
 
+impl From<prover::Error> for Error {
+    fn from(e: prover::Error) -> Self {
+        Error::Prover(e)
+    }
+}
+
 impl From<value::OverflowError> for Error {
     fn from(e: value::OverflowError) -> Self {
         Error::ValueSum(e)
@@ -83,6 +111,23 @@ impl SpendInfo {
             merkle_path,
         }
     }
+
+    /// Generates a dummy spent note of the given asset.
+    ///
+    /// Bundles that transfer a non-native asset pad that asset's spends with these, so
+    /// that every action spends and creates notes of the same asset.
+    #[cfg(feature = "zsa")]
+    fn dummy_with_asset(asset: AssetBase, rng: &mut impl RngCore) -> Self {
+        let mut spend = Self::dummy(rng);
+        spend.note = Note::new_with_asset(
+            spend.note.recipient(),
+            spend.note.value(),
+            asset,
+            spend.note.rho(),
+            rng,
+        );
+        spend
+    }
 }
 
 /// Information about a specific recipient to receive funds in an [`Action`].
@@ -91,6 +136,8 @@ struct RecipientInfo {
     ovk: Option<OutgoingViewingKey>,
     recipient: Address,
     value: NoteValue,
+    #[cfg(feature = "zsa")]
+    asset: AssetBase,
     memo: Option<[u8; 512]>,
 }
 
@@ -106,9 +153,20 @@ impl RecipientInfo {
             ovk: None,
             recipient,
             value: NoteValue::zero(),
+            #[cfg(feature = "zsa")]
+            asset: AssetBase::native(),
             memo: None,
         }
     }
+
+    /// Generates a dummy recipient of the given asset.
+    #[cfg(feature = "zsa")]
+    fn dummy_with_asset(asset: AssetBase, rng: &mut impl RngCore) -> Self {
+        RecipientInfo {
+            asset,
+            ..Self::dummy(rng)
+        }
+    }
 }
 
 /// Information about a specific [`Action`] we plan to build.
@@ -140,7 +198,11 @@ impl ActionInfo {
     /// [orchardsend]: https://zips.z.cash/protocol/nu5.pdf#orchardsend
     fn build(self, mut rng: impl RngCore) -> (Action<SigningMetadata>, Circuit) {
         let v_net = self.value_sum();
+        #[cfg(not(feature = "zsa"))]
         let cv_net = ValueCommitment::derive(v_net, self.rcv.clone());
+        #[cfg(feature = "zsa")]
+        let cv_net =
+            ValueCommitment::derive_with_asset(v_net, self.rcv.clone(), self.spend.note.asset());
 
         let nf_old = self.spend.note.nullifier(&self.spend.fvk);
         let sender_address = self.spend.note.recipient();
@@ -151,7 +213,16 @@ impl ActionInfo {
         let alpha = pallas::Scalar::random(&mut rng);
         let rk = ak.randomize(&alpha);
 
+        #[cfg(not(feature = "zsa"))]
         let note = Note::new(self.output.recipient, self.output.value, nf_old, &mut rng);
+        #[cfg(feature = "zsa")]
+        let note = Note::new_with_asset(
+            self.output.recipient,
+            self.output.value,
+            self.output.asset,
+            nf_old,
+            &mut rng,
+        );
         let cm_new = note.commitment();
         let cmx = cm_new.into();
 
@@ -183,7 +254,7 @@ impl ActionInfo {
                     dummy_ask: self.spend.dummy_sk.as_ref().map(SpendAuthorizingKey::from),
                     parts: SigningParts {
                         ak: ak.clone(),
-                        alpha,
+                        alpha: SecretField::new(alpha),
                     },
                 },
             ),
@@ -194,17 +265,17 @@ impl ActionInfo {
                 pk_d_old: Some(*sender_address.pk_d()),
                 v_old: Some(self.spend.note.value()),
                 rho_old: Some(rho_old),
-                psi_old: Some(psi_old),
+                psi_old: Some(SecretField::new(psi_old)),
                 rcm_old: Some(rcm_old),
                 cm_old: Some(self.spend.note.commitment()),
-                alpha: Some(alpha),
+                alpha: Some(SecretField::new(alpha)),
                 ak: Some(ak),
-                nk: Some(*self.spend.fvk.nk()),
+                nk: Some(self.spend.fvk.nk().clone()),
                 rivk: Some(self.spend.fvk.rivk(self.spend.scope)),
                 g_d_new: Some(note.recipient().g_d()),
                 pk_d_new: Some(*note.recipient().pk_d()),
                 v_new: Some(note.value()),
-                psi_new: Some(note.rseed().psi(&note.rho())),
+                psi_new: Some(SecretField::new(note.rseed().psi(&note.rho()))),
                 rcm_new: Some(note.rseed().rcm(&note.rho())),
                 rcv: Some(self.rcv),
             },
@@ -287,12 +358,109 @@ impl Builder {
             ovk,
             recipient,
             value,
+            #[cfg(feature = "zsa")]
+            asset: AssetBase::native(),
+            memo,
+        });
+
+        Ok(())
+    }
+
+    /// Adds an address which will receive funds of the given asset in this transaction.
+    ///
+    /// The total value sent of each non-native asset must equal the total value of the
+    /// notes of that asset added with [`Builder::add_spend`]; this is checked by
+    /// [`Builder::build`].
+    ///
+    /// The memo of a note of a non-native asset is limited to
+    /// [`ZSA_MEMO_SIZE`](crate::note_encryption::ZSA_MEMO_SIZE) bytes; a memo with any
+    /// non-zero byte beyond that is rejected.
+    #[cfg(feature = "zsa")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "zsa")))]
+    pub fn add_recipient_with_asset(
+        &mut self,
+        ovk: Option<OutgoingViewingKey>,
+        recipient: Address,
+        value: NoteValue,
+        asset: AssetBase,
+        memo: Option<[u8; 512]>,
+    ) -> Result<(), &'static str> {
+        if !self.flags.outputs_enabled() {
+            return Err("Outputs are not enabled for this builder");
+        }
+
+        if !bool::from(asset.is_native())
+            && memo.map_or(false, |memo| {
+                memo[crate::note_encryption::ZSA_MEMO_SIZE..]
+                    .iter()
+                    .any(|b| *b != 0)
+            })
+        {
+            return Err("Memos of notes of non-native assets are limited to 480 bytes");
+        }
+
+        self.recipients.push(RecipientInfo {
+            ovk,
+            recipient,
+            value,
+            asset,
             memo,
         });
 
         Ok(())
     }
 
+    /// Returns the number of actions that [`Builder::build`] will produce from the spends
+    /// and recipients added so far.
+    ///
+    /// This is the number of ZIP 317 logical actions contributed by the bundle.
+    pub fn logical_actions(&self) -> usize {
+        self.num_actions()
+    }
+
+    /// Returns the fee required by `fee_rule` for a transaction containing the bundle
+    /// that [`Builder::build`] will produce.
+    ///
+    /// Wallets can use this to choose the value of a change output before building, as
+    /// long as adding it does not change [`Builder::logical_actions`].
+    pub fn fee_required<F: FeeRule>(&self, fee_rule: &F) -> Result<NoteValue, OverflowError> {
+        let actions = self.num_actions();
+        fee_rule.fee_required(actions, actions)
+    }
+
+    #[cfg(not(feature = "zsa"))]
+    fn num_actions(&self) -> usize {
+        self.spends
+            .len()
+            .max(self.recipients.len())
+            .max(MIN_ACTIONS)
+    }
+
+    #[cfg(feature = "zsa")]
+    fn num_actions(&self) -> usize {
+        // Mirrors the padding applied by `pair_by_asset`.
+        let mut counts: BTreeMap<[u8; 32], (usize, usize)> = BTreeMap::new();
+        for spend in &self.spends {
+            counts.entry(spend.note.asset().to_bytes()).or_default().0 += 1;
+        }
+        for recipient in &self.recipients {
+            counts.entry(recipient.asset.to_bytes()).or_default().1 += 1;
+        }
+
+        let (native_spends, native_recipients) = counts
+            .remove(&AssetBase::native().to_bytes())
+            .unwrap_or_default();
+        let num_non_native: usize = counts
+            .values()
+            .map(|(spends, recipients)| *spends.max(recipients))
+            .sum();
+
+        num_non_native
+            + native_spends
+                .max(native_recipients)
+                .max(MIN_ACTIONS.saturating_sub(num_non_native))
+    }
+
     /// Builds a bundle containing the given spent notes and recipients.
     ///
     /// The returned bundle will have no proof or signatures; these can be applied with
@@ -302,6 +470,7 @@ impl Builder {
         mut rng: impl RngCore,
     ) -> Result<Bundle<InProgress<Unproven, Unauthorized>, V>, Error> {
         // Pair up the spends and recipients, extending with dummy values as necessary.
+        #[cfg(not(feature = "zsa"))]
         let pre_actions: Vec<_> = {
             let num_spends = self.spends.len();
             let num_recipients = self.recipients.len();
@@ -331,6 +500,12 @@ impl Builder {
                 .map(|(spend, recipient)| ActionInfo::new(spend, recipient, &mut rng))
                 .collect()
         };
+        #[cfg(feature = "zsa")]
+        let pre_actions = pair_by_asset(self.spends, self.recipients, &mut rng)?;
+        #[cfg(feature = "zsa")]
+        let contains_non_native = pre_actions
+            .iter()
+            .any(|a| !bool::from(a.spend.note.asset().is_native()));
 
         // Move some things out of self that we will need.
         let flags = self.flags;
@@ -371,13 +546,107 @@ impl Builder {
             result_value_balance,
             anchor,
             InProgress {
-                proof: Unproven { circuits },
+                proof: Unproven {
+                    circuits,
+                    #[cfg(feature = "zsa")]
+                    contains_non_native,
+                },
                 sigs: Unauthorized { bsk },
             },
         ))
     }
 }
 
+/// Pairs up spends and recipients such that every action spends and creates notes of a
+/// single asset, extending each asset with dummy values as necessary.
+///
+/// Returns an error if the spends and recipients of any non-native asset do not balance,
+/// as only the native asset may contribute to the bundle's value balance.
+#[cfg(feature = "zsa")]
+fn pair_by_asset(
+    spends: Vec<SpendInfo>,
+    recipients: Vec<RecipientInfo>,
+    mut rng: impl RngCore,
+) -> Result<Vec<ActionInfo>, Error> {
+    let native = AssetBase::native();
+    let mut by_asset: BTreeMap<[u8; 32], (AssetBase, Vec<SpendInfo>, Vec<RecipientInfo>)> =
+        BTreeMap::new();
+    // The native asset is always present, so that the bundle meets the minimum number of
+    // actions even if it only transfers non-native assets.
+    by_asset.insert(native.to_bytes(), (native, vec![], vec![]));
+    for spend in spends {
+        let asset = spend.note.asset();
+        by_asset
+            .entry(asset.to_bytes())
+            .or_insert_with(|| (asset, vec![], vec![]))
+            .1
+            .push(spend);
+    }
+    for recipient in recipients {
+        let asset = recipient.asset;
+        by_asset
+            .entry(asset.to_bytes())
+            .or_insert_with(|| (asset, vec![], vec![]))
+            .2
+            .push(recipient);
+    }
+
+    let num_non_native: usize = by_asset
+        .values()
+        .filter(|(asset, _, _)| *asset != native)
+        .map(|(_, spends, recipients)| spends.len().max(recipients.len()))
+        .sum();
+
+    let mut pre_actions = vec![];
+    for (asset, mut spends, mut recipients) in by_asset.into_values() {
+        let min_actions = if asset == native {
+            MIN_ACTIONS.saturating_sub(num_non_native)
+        } else {
+            // Note values are 64-bit, so these sums cannot overflow.
+            let spent: u128 = spends
+                .iter()
+                .map(|s| u128::from(s.note.value().inner()))
+                .sum();
+            let sent: u128 = recipients.iter().map(|r| u128::from(r.value.inner())).sum();
+            if spent != sent {
+                return Err(Error::UnbalancedAsset(asset));
+            }
+            0
+        };
+
+        let num_spends = spends.len();
+        let num_recipients = recipients.len();
+        let num_actions = [num_spends, num_recipients, min_actions]
+            .iter()
+            .max()
+            .cloned()
+            .unwrap();
+
+        spends.extend(
+            iter::repeat_with(|| SpendInfo::dummy_with_asset(asset, &mut rng))
+                .take(num_actions - num_spends),
+        );
+        recipients.extend(
+            iter::repeat_with(|| RecipientInfo::dummy_with_asset(asset, &mut rng))
+                .take(num_actions - num_recipients),
+        );
+
+        spends.shuffle(&mut rng);
+        recipients.shuffle(&mut rng);
+        pre_actions.extend(
+            spends
+                .into_iter()
+                .zip(recipients.into_iter())
+                .map(|(spend, recipient)| ActionInfo::new(spend, recipient, &mut rng)),
+        );
+    }
+
+    // Shuffle the actions, so that their order doesn't reveal which assets are
+    // transferred in which actions.
+    pre_actions.shuffle(&mut rng);
+    Ok(pre_actions)
+}
+
 /// Marker trait representing bundle signatures in the process of being created.
 pub trait InProgressSignatures: fmt::Debug {
     /// The authorization type of an Orchard action in the process of being authorized.
@@ -401,6 +670,8 @@ impl<P: fmt::Debug, S: InProgressSignatures> Authorization for InProgress<P, S>
 #[derive(Clone, Debug)]
 pub struct Unproven {
     circuits: Vec<Circuit>,
+    #[cfg(feature = "zsa")]
+    contains_non_native: bool,
 }
 
 impl<S: InProgressSignatures> InProgress<Unproven, S> {
@@ -427,6 +698,10 @@ impl<S: InProgressSignatures, V> Bundle<InProgress<Unproven, S>, V> {
             .iter()
             .map(|a| a.to_instance(*self.flags(), *self.anchor()))
             .collect();
+        #[cfg(feature = "zsa")]
+        if self.authorization().proof.contains_non_native {
+            return Err(Error::NonNativeAssetProof);
+        }
         self.try_map_authorization(
             &mut (),
             |_, _, a| Ok(a),
@@ -439,16 +714,102 @@ impl<S: InProgressSignatures, V> Bundle<InProgress<Unproven, S>, V> {
             },
         )
     }
+
+    /// Returns a request to prove this bundle, containing the witnesses and public
+    /// inputs of its Action circuits.
+    ///
+    /// The request can be proven elsewhere with [`Proof::create_from_request`], and the
+    /// resulting proof added to this bundle with [`Bundle::apply_proof`].
+    pub fn proving_request(&self) -> Result<ProvingRequest, Error> {
+        #[cfg(feature = "zsa")]
+        if self.authorization().proof.contains_non_native {
+            return Err(Error::NonNativeAssetProof);
+        }
+        Ok(ProvingRequest::from_parts(
+            self.authorization().proof.circuits.clone(),
+            self.to_instances(),
+        ))
+    }
+
+    /// Adds a proof created from this bundle's [`Bundle::proving_request`].
+    ///
+    /// The proof is verified against the bundle's public inputs with `vk`, so that a
+    /// bundle is never signed with a proof from a faulty or malicious prover.
+    pub fn apply_proof(
+        self,
+        vk: &VerifyingKey,
+        proof: Proof,
+    ) -> Result<Bundle<InProgress<Proof, S>, V>, Error> {
+        proof.verify(vk, &self.to_instances())?;
+        Ok(self.map_authorization(
+            &mut (),
+            |_, _, a| a,
+            |_, auth| InProgress {
+                proof,
+                sigs: auth.sigs,
+            },
+        ))
+    }
+
+    /// Creates the proof for this bundle with the given [`Prover`], which may be a
+    /// [`ProvingKey`] or a client for a prover service.
+    ///
+    /// The proof is checked with `vk` as in [`Bundle::apply_proof`].
+    ///
+    /// [`Prover`]: crate::prover::Prover
+    pub fn create_proof_with<P: Prover>(
+        self,
+        mut prover: P,
+        vk: &VerifyingKey,
+    ) -> Result<Bundle<InProgress<Proof, S>, V>, Error> {
+        let proof = prover.prove(&self.proving_request()?)?;
+        self.apply_proof(vk, proof)
+    }
+
+    /// Checks the witness for each action against the public inputs that
+    /// [`Bundle::create_proof`] would use, without creating a proof.
+    ///
+    /// Returns the index of each action whose witness does not satisfy the Action
+    /// circuit, together with the reasons why. This is slow, and intended for debugging
+    /// bundles for which proof creation or verification fails.
+    pub fn diagnose_proof(&self) -> Result<(), Vec<(usize, Vec<WitnessFailure>)>> {
+        let failures: Vec<_> = self
+            .actions()
+            .iter()
+            .zip(self.authorization().proof.circuits.iter())
+            .enumerate()
+            .filter_map(|(i, (action, circuit))| {
+                circuit
+                    .diagnose(&action.to_instance(*self.flags(), *self.anchor()))
+                    .err()
+                    .map(|failures| (i, failures))
+            })
+            .collect();
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
 }
 
 /// The parts needed to sign an [`Action`].
+///
+/// The randomizer `alpha` is zeroized when this is dropped.
 #[derive(Clone, Debug)]
 pub struct SigningParts {
     /// The spend validating key for this action. Used to match spend authorizing keys to
     /// actions they can create signatures for.
     ak: SpendValidatingKey,
     /// The randomization needed to derive the actual signing key for this note.
-    alpha: pallas::Scalar,
+    alpha: SecretField<pallas::Scalar>,
+}
+
+impl Drop for SigningParts {
+    fn drop(&mut self) {
+        self.alpha.zeroize();
+    }
 }
 
 /// Marker for an unauthorized bundle with no signatures.
@@ -744,7 +1105,7 @@ pub mod testing {
 
             ArbitraryBundleInputs {
                 rng: StdRng::from_seed(rng_seed),
-                sk,
+                sk: sk.clone(),
                 anchor: tree.root(0).unwrap().into(),
                 notes: notes_and_auth_paths,
                 recipient_amounts
@@ -774,8 +1135,9 @@ mod tests {
     use super::Builder;
     use crate::{
         bundle::{Authorized, Bundle, Flags},
-        circuit::ProvingKey,
+        circuit::{Proof, ProvingKey, ProvingRequest, VerifyingKey},
         constants::MERKLE_DEPTH_ORCHARD,
+        fees::{self, FeeRule, Zip317FeeRule},
         keys::{FullViewingKey, Scope, SpendingKey},
         tree::EMPTY_ROOTS,
         value::NoteValue,
@@ -808,4 +1170,97 @@ mod tests {
             .unwrap();
         assert_eq!(bundle.value_balance(), &(-5000))
     }
+
+    #[test]
+    fn zip317_logical_actions() {
+        let mut rng = OsRng;
+
+        let sk = SpendingKey::random(&mut rng);
+        let fvk = FullViewingKey::from(&sk);
+        let recipient = fvk.address_at(0u32, Scope::External);
+        let rule = Zip317FeeRule::standard();
+
+        let mut builder = Builder::new(
+            Flags::from_parts(true, true),
+            EMPTY_ROOTS[MERKLE_DEPTH_ORCHARD].into(),
+        );
+        // An empty builder still produces a padded bundle, and the free functions agree.
+        assert_eq!(builder.logical_actions(), 2);
+        assert_eq!(builder.logical_actions(), fees::logical_actions(0, 0));
+        assert_eq!(
+            builder.fee_required(&rule).unwrap().inner(),
+            rule.fee_required(0, 0).unwrap().inner()
+        );
+        let empty: Bundle<_, i64> = Builder::new(
+            Flags::from_parts(true, true),
+            EMPTY_ROOTS[MERKLE_DEPTH_ORCHARD].into(),
+        )
+        .build(&mut rng)
+        .unwrap();
+        assert_eq!(empty.logical_actions(), fees::logical_actions(0, 0));
+
+        for _ in 0..3 {
+            builder
+                .add_recipient(None, recipient, NoteValue::from_raw(5000), None)
+                .unwrap();
+        }
+        assert_eq!(builder.logical_actions(), 3);
+        assert_eq!(builder.fee_required(&rule).unwrap().inner(), 15_000);
+
+        let bundle: Bundle<_, i64> = builder.build(&mut rng).unwrap();
+        assert_eq!(bundle.logical_actions(), 3);
+        assert_eq!(bundle.fee_required(&rule).unwrap().inner(), 15_000);
+    }
+
+    #[test]
+    fn outsourced_proving() {
+        let mut rng = OsRng;
+
+        let sk = SpendingKey::random(&mut rng);
+        let fvk = FullViewingKey::from(&sk);
+        let recipient = fvk.address_at(0u32, Scope::External);
+
+        let mut builder = Builder::new(
+            Flags::from_parts(true, true),
+            EMPTY_ROOTS[MERKLE_DEPTH_ORCHARD].into(),
+        );
+        builder
+            .add_recipient(None, recipient, NoteValue::from_raw(5000), None)
+            .unwrap();
+        let bundle: Bundle<_, i64> = builder.build(&mut rng).unwrap();
+
+        // The request survives serialization.
+        let mut encoded = vec![];
+        bundle
+            .proving_request()
+            .unwrap()
+            .write(&mut encoded)
+            .unwrap();
+        let request = ProvingRequest::read(&encoded[..]).unwrap();
+        assert_eq!(request.instances().len(), 2);
+        assert!(ProvingRequest::read(&encoded[..encoded.len() - 1]).is_err());
+
+        let proof = Proof::create_from_request(&ProvingKey::fake(), &request, &mut rng).unwrap();
+
+        // A proof for a different bundle is rejected.
+        let mut other = Builder::new(
+            Flags::from_parts(true, true),
+            EMPTY_ROOTS[MERKLE_DEPTH_ORCHARD].into(),
+        );
+        other
+            .add_recipient(None, recipient, NoteValue::from_raw(5000), None)
+            .unwrap();
+        let other: Bundle<_, i64> = other.build(&mut rng).unwrap();
+        assert!(other
+            .apply_proof(&VerifyingKey::fake(), proof.clone())
+            .is_err());
+
+        let bundle: Bundle<Authorized, i64> = bundle
+            .apply_proof(&VerifyingKey::fake(), proof)
+            .unwrap()
+            .prepare(&mut rng, [0; 32])
+            .finalize()
+            .unwrap();
+        assert!(bundle.verify_proof(&VerifyingKey::fake()).is_ok());
+    }
 }
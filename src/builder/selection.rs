@@ -0,0 +1,644 @@
+//! Selection of notes to spend in an Orchard bundle.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::{
+    builder::{Builder, MIN_ACTIONS},
+    bundle::Flags,
+    fees::FeeRule,
+    keys::{FullViewingKey, OutgoingViewingKey, Scope},
+    note::Note,
+    tree::{Anchor, MerklePath},
+    value::{NoteValue, OverflowError},
+    Address,
+};
+
+/// An error that can occur while selecting notes.
+#[derive(Debug)]
+pub enum Error {
+    /// The spendable notes do not have enough value to make the payments and pay the
+    /// fee.
+    InsufficientFunds {
+        /// The total value of the spendable notes.
+        available: NoteValue,
+        /// The value required to make the payments and pay the fee with every
+        /// spendable note.
+        required: NoteValue,
+    },
+    /// A value operation overflowed.
+    Overflow,
+    /// A selected note could not be added to a [`Builder`].
+    Builder(&'static str),
+    /// The maximum number of actions per bundle is smaller than the number of actions
+    /// that every bundle contains.
+    ActionLimit(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InsufficientFunds {
+                available,
+                required,
+            } => write!(
+                f,
+                "Insufficient funds: {} zatoshis available, {} required",
+                available.inner(),
+                required.inner()
+            ),
+            Error::Overflow => write!(f, "{}", OverflowError),
+            Error::Builder(e) => write!(f, "Could not add selected notes to builder: {}", e),
+            Error::ActionLimit(limit) => write!(
+                f,
+                "Bundles must be allowed at least {} actions, not {}",
+                MIN_ACTIONS, limit
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<OverflowError> for Error {
+    fn from(_: OverflowError) -> Self {
+        Error::Overflow
+    }
+}
+
+/// A note that the wallet is able to spend.
+#[derive(Clone, Debug)]
+pub struct SpendableNote {
+    fvk: FullViewingKey,
+    note: Note,
+    merkle_path: MerklePath,
+}
+
+impl SpendableNote {
+    /// Constructs a spendable note from the full viewing key that received it, and its
+    /// Merkle path to the anchor that the bundle will use.
+    pub fn new(fvk: FullViewingKey, note: Note, merkle_path: MerklePath) -> Self {
+        SpendableNote {
+            fvk,
+            note,
+            merkle_path,
+        }
+    }
+
+    /// Returns the full viewing key that received this note.
+    pub fn fvk(&self) -> &FullViewingKey {
+        &self.fvk
+    }
+
+    /// Returns the note.
+    pub fn note(&self) -> &Note {
+        &self.note
+    }
+
+    /// Returns the Merkle path for this note.
+    pub fn merkle_path(&self) -> &MerklePath {
+        &self.merkle_path
+    }
+
+    /// Returns the value of this note.
+    pub fn value(&self) -> NoteValue {
+        self.note.value()
+    }
+}
+
+/// A payment to be made from the selected notes.
+#[derive(Clone, Debug)]
+pub struct Payment {
+    recipient: Address,
+    value: NoteValue,
+    memo: Option<[u8; 512]>,
+}
+
+impl Payment {
+    /// Constructs a payment of `value` to `recipient`, with an optional memo.
+    pub fn new(recipient: Address, value: NoteValue, memo: Option<[u8; 512]>) -> Self {
+        Payment {
+            recipient,
+            value,
+            memo,
+        }
+    }
+
+    /// Returns the recipient of this payment.
+    pub fn recipient(&self) -> Address {
+        self.recipient
+    }
+
+    /// Returns the value of this payment.
+    pub fn value(&self) -> NoteValue {
+        self.value
+    }
+
+    /// Returns the memo of this payment, if any.
+    pub fn memo(&self) -> Option<&[u8; 512]> {
+        self.memo.as_ref()
+    }
+}
+
+/// The goal that note selection optimizes for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    /// Spend the fewest notes that cover the payments and fee, largest first.
+    ///
+    /// Under ZIP 317 every action is charged for, but a bundle always has as many
+    /// actions as its larger of spends and outputs (and at least two). When there are
+    /// fewer spends than that, the remaining actions would be padded with dummy spends,
+    /// so this strategy fills them with the smallest other notes instead. This
+    /// consolidates small notes without increasing the fee.
+    MinimizeActions,
+    /// Spend as few notes together as possible, so that fewer of the wallet's notes
+    /// are linked by being spent in the same transaction.
+    ///
+    /// If any single note covers the payments and fee, the smallest such note is spent.
+    /// Otherwise the fewest notes are spent, largest first, and no further notes are
+    /// added.
+    ReduceLinkability,
+}
+
+/// The result of note selection: the notes to spend, the outputs to create, and the fee.
+#[derive(Clone, Debug)]
+pub struct Plan {
+    spends: Vec<SpendableNote>,
+    payments: Vec<Payment>,
+    change: Option<(Address, NoteValue)>,
+    ovk: Option<OutgoingViewingKey>,
+    fee: NoteValue,
+}
+
+impl Plan {
+    /// Returns the notes to spend.
+    pub fn spends(&self) -> &[SpendableNote] {
+        &self.spends
+    }
+
+    /// Returns the payments to make.
+    pub fn payments(&self) -> &[Payment] {
+        &self.payments
+    }
+
+    /// Returns the value of the change output, or `None` if the plan has no change.
+    pub fn change(&self) -> Option<NoteValue> {
+        self.change.map(|(_, value)| value)
+    }
+
+    /// Returns the fee that this plan pays.
+    ///
+    /// This may exceed the fee required by the fee rule, if the excess value was too
+    /// small to pay for a change output.
+    pub fn fee(&self) -> NoteValue {
+        self.fee
+    }
+
+    /// Returns the number of actions in a bundle built from this plan.
+    pub fn actions(&self) -> usize {
+        let outputs = self.payments.len() + usize::from(self.change.is_some());
+        self.spends.len().max(outputs).max(MIN_ACTIONS)
+    }
+
+    /// Adds the spends and outputs of this plan to `builder`.
+    ///
+    /// The Merkle paths of the spent notes must lead to the builder's anchor.
+    pub fn add_to(self, builder: &mut Builder) -> Result<(), Error> {
+        for spend in self.spends {
+            builder
+                .add_spend(spend.fvk, spend.note, spend.merkle_path)
+                .map_err(Error::Builder)?;
+        }
+        for payment in self.payments {
+            builder
+                .add_recipient(
+                    self.ovk.clone(),
+                    payment.recipient,
+                    payment.value,
+                    payment.memo,
+                )
+                .map_err(Error::Builder)?;
+        }
+        if let Some((address, value)) = self.change {
+            builder
+                .add_recipient(self.ovk, address, value, None)
+                .map_err(Error::Builder)?;
+        }
+        Ok(())
+    }
+
+    /// Creates a [`Builder`] with spends and outputs enabled, and adds this plan to it.
+    pub fn into_builder(self, anchor: Anchor) -> Result<Builder, Error> {
+        let mut builder = Builder::new(Flags::from_parts(true, true), anchor);
+        self.add_to(&mut builder)?;
+        Ok(builder)
+    }
+}
+
+/// Selects notes from `notes` to make `payments` and pay the fee required by
+/// `fee_rule`.
+///
+/// Any value left over after the payments and fee is sent to `change_address`. If the
+/// leftover value would not cover the additional fee for a change output, it is added
+/// to the fee instead. The outputs of the plan are encrypted to `ovk`.
+pub fn select_notes<F: FeeRule>(
+    mut notes: Vec<SpendableNote>,
+    payments: Vec<Payment>,
+    change_address: Address,
+    ovk: Option<OutgoingViewingKey>,
+    fee_rule: &F,
+    strategy: Strategy,
+) -> Result<Plan, Error> {
+    let target = sum(payments.iter().map(|p| p.value))?;
+    let try_settle = |selected: &[SpendableNote]| -> Result<Option<Settlement>, Error> {
+        settle(
+            sum(selected.iter().map(|n| n.value()))?,
+            target,
+            selected.len(),
+            payments.len(),
+            fee_rule,
+        )
+    };
+
+    // Smallest notes first; we select from the end to spend the fewest notes.
+    notes.sort_by_key(|n| n.value().inner());
+
+    // With `ReduceLinkability`, prefer the smallest note that covers everything alone.
+    let mut single = None;
+    if strategy == Strategy::ReduceLinkability {
+        for (i, note) in notes.iter().enumerate().rev() {
+            if try_settle(core::slice::from_ref(note))?.is_none() {
+                break;
+            }
+            single = Some(i);
+        }
+    }
+
+    let mut selected = vec![];
+    let mut settlement = None;
+    if let Some(i) = single {
+        selected.push(notes.remove(i));
+        settlement = try_settle(&selected)?;
+    } else {
+        while let Some(note) = notes.pop() {
+            selected.push(note);
+            settlement = try_settle(&selected)?;
+            if settlement.is_some() {
+                break;
+            }
+        }
+    }
+
+    let mut settlement = match settlement {
+        Some(settlement) => settlement,
+        None => {
+            let fee = fee_rule.fee_required(selected.len(), payments.len())?;
+            return Err(Error::InsufficientFunds {
+                available: sum(selected.iter().map(|n| n.value()))?,
+                required: sum([target, fee].iter().copied())?,
+            });
+        }
+    };
+
+    if strategy == Strategy::MinimizeActions {
+        // Fill the actions that would otherwise contain dummy spends with the smallest
+        // remaining notes, as long as this does not add an action.
+        while !notes.is_empty() {
+            let actions = settlement.actions(selected.len(), payments.len());
+            selected.push(notes.remove(0));
+            match try_settle(&selected)? {
+                Some(next) if next.actions(selected.len(), payments.len()) <= actions => {
+                    settlement = next;
+                }
+                _ => {
+                    selected.pop();
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(Plan {
+        spends: selected,
+        payments,
+        change: settlement.change.map(|value| (change_address, value)),
+        ovk,
+        fee: settlement.fee,
+    })
+}
+
+/// A plan for merging many notes into fewer notes, each in its own bundle.
+///
+/// Created by [`consolidate`].
+#[derive(Clone, Debug)]
+pub struct Consolidation {
+    plans: Vec<Plan>,
+    remaining: Vec<SpendableNote>,
+}
+
+impl Consolidation {
+    /// Returns the plan for each bundle.
+    ///
+    /// Every plan spends notes received by a single full viewing key, and sends their
+    /// value minus the fee as change to that key's internal address.
+    pub fn plans(&self) -> &[Plan] {
+        &self.plans
+    }
+
+    /// Returns the notes that are not consolidated, because spending them would cost
+    /// more in fees than they are worth.
+    pub fn remaining(&self) -> &[SpendableNote] {
+        &self.remaining
+    }
+
+    /// Returns the total fee paid by all of the bundles.
+    pub fn fee(&self) -> Result<NoteValue, Error> {
+        sum(self.plans.iter().map(|plan| plan.fee))
+    }
+
+    /// Creates a [`Builder`] for each bundle, in the order of [`Consolidation::plans`].
+    ///
+    /// Each builder is for a separate transaction, which must pay the fee of its plan.
+    pub fn into_builders(self, anchor: Anchor) -> Result<Vec<Builder>, Error> {
+        self.plans
+            .into_iter()
+            .map(|plan| plan.into_builder(anchor))
+            .collect()
+    }
+}
+
+/// Plans the consolidation of `notes` into bundles of at most `max_actions` actions.
+///
+/// The notes received by each full viewing key are merged into notes sent to the
+/// internal address of that key, with the change encrypted to its internal outgoing
+/// viewing key. Each bundle pays the fee required by `fee_rule` for its own transaction.
+///
+/// The largest notes are consolidated first. A note is left out if adding it to a
+/// bundle would increase the fee by at least its value, as is a final bundle whose notes
+/// cannot pay for its fee and output; these notes are returned by
+/// [`Consolidation::remaining`].
+///
+/// The Merkle paths of all notes must lead to the anchor that the builders will use.
+pub fn consolidate<F: FeeRule>(
+    notes: Vec<SpendableNote>,
+    max_actions: usize,
+    fee_rule: &F,
+) -> Result<Consolidation, Error> {
+    if max_actions < MIN_ACTIONS {
+        return Err(Error::ActionLimit(max_actions));
+    }
+
+    let mut by_fvk: BTreeMap<FullViewingKey, Vec<SpendableNote>> = BTreeMap::new();
+    for note in notes {
+        by_fvk.entry(note.fvk.clone()).or_default().push(note);
+    }
+
+    let mut plans = vec![];
+    let mut remaining = vec![];
+    for (fvk, mut notes) in by_fvk {
+        let change_address = fvk.address_at(0u32, Scope::Internal);
+        let ovk = fvk.to_ovk(Scope::Internal);
+        let plan_for = |spends: Vec<SpendableNote>| -> Result<Result<Plan, _>, Error> {
+            let total = sum(spends.iter().map(|n| n.value()))?;
+            Ok(
+                match settle(total, NoteValue::zero(), spends.len(), 0, fee_rule)? {
+                    Some(Settlement {
+                        fee,
+                        change: Some(change),
+                    }) => Ok(Plan {
+                        spends,
+                        payments: vec![],
+                        change: Some((change_address, change)),
+                        ovk: Some(ovk.clone()),
+                        fee,
+                    }),
+                    // Without a change output the value would all go to the fee.
+                    _ => Err(spends),
+                },
+            )
+        };
+
+        // Largest notes first.
+        notes.sort_by_key(|n| core::cmp::Reverse(n.value().inner()));
+
+        let mut chunk = vec![];
+        let mut notes = notes.into_iter();
+        while let Some(note) = notes.next() {
+            let marginal_fee = fee_rule.fee_required(chunk.len() + 1, 1)?.inner()
+                - fee_rule.fee_required(chunk.len(), 1)?.inner();
+            if note.value().inner() <= marginal_fee {
+                // The notes are sorted, so none of the rest are worth spending either.
+                remaining.push(note);
+                remaining.extend(notes);
+                break;
+            }
+
+            chunk.push(note);
+            if chunk.len() == max_actions {
+                match plan_for(core::mem::take(&mut chunk))? {
+                    Ok(plan) => plans.push(plan),
+                    Err(spends) => remaining.extend(spends),
+                }
+            }
+        }
+        if !chunk.is_empty() {
+            match plan_for(chunk)? {
+                Ok(plan) => plans.push(plan),
+                Err(spends) => remaining.extend(spends),
+            }
+        }
+    }
+
+    Ok(Consolidation { plans, remaining })
+}
+
+/// How the value of a set of selected notes is split between the fee and change.
+#[derive(Clone, Copy, Debug)]
+struct Settlement {
+    fee: NoteValue,
+    change: Option<NoteValue>,
+}
+
+impl Settlement {
+    fn actions(&self, spends: usize, payments: usize) -> usize {
+        let outputs = payments + usize::from(self.change.is_some());
+        spends.max(outputs).max(MIN_ACTIONS)
+    }
+}
+
+/// Determines how `total` covers `target` and the fee for a bundle with `spends` spends
+/// and `payments` payments, or returns `None` if it does not.
+fn settle<F: FeeRule>(
+    total: NoteValue,
+    target: NoteValue,
+    spends: usize,
+    payments: usize,
+    fee_rule: &F,
+) -> Result<Option<Settlement>, Error> {
+    let excess = match total.inner().checked_sub(target.inner()) {
+        Some(excess) => excess,
+        None => return Ok(None),
+    };
+
+    let fee = fee_rule.fee_required(spends, payments)?.inner();
+    let fee_with_change = fee_rule.fee_required(spends, payments + 1)?.inner();
+    Ok(if excess > fee_with_change {
+        Some(Settlement {
+            fee: NoteValue::from_raw(fee_with_change),
+            change: Some(NoteValue::from_raw(excess - fee_with_change)),
+        })
+    } else if excess >= fee {
+        Some(Settlement {
+            fee: NoteValue::from_raw(excess),
+            change: None,
+        })
+    } else {
+        None
+    })
+}
+
+fn sum(values: impl Iterator<Item = NoteValue>) -> Result<NoteValue, Error> {
+    values
+        .try_fold(0u64, |acc, v| acc.checked_add(v.inner()))
+        .map(NoteValue::from_raw)
+        .ok_or(Error::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::OsRng;
+
+    use super::{consolidate, select_notes, Error, Payment, SpendableNote, Strategy};
+    use crate::{
+        fees::Zip317FeeRule,
+        keys::{FullViewingKey, Scope, SpendingKey},
+        note::{Note, Nullifier},
+        tree::MerklePath,
+        value::NoteValue,
+    };
+
+    fn notes(fvk: &FullViewingKey, values: &[u64]) -> Vec<SpendableNote> {
+        let mut rng = OsRng;
+        let recipient = fvk.address_at(0u32, Scope::External);
+        values
+            .iter()
+            .map(|value| {
+                let note = Note::new(
+                    recipient,
+                    NoteValue::from_raw(*value),
+                    Nullifier::dummy(&mut rng),
+                    &mut rng,
+                );
+                SpendableNote::new(fvk.clone(), note, MerklePath::dummy(&mut rng))
+            })
+            .collect()
+    }
+
+    fn values(spends: &[SpendableNote]) -> Vec<u64> {
+        let mut values: Vec<_> = spends.iter().map(|n| n.value().inner()).collect();
+        values.sort_unstable();
+        values
+    }
+
+    #[test]
+    fn selection_strategies() {
+        let sk = SpendingKey::from_bytes([7; 32]).unwrap();
+        let fvk = FullViewingKey::from(&sk);
+        let change = fvk.address_at(0u32, Scope::Internal);
+        let recipient = fvk.address_at(1u32, Scope::External);
+        let rule = Zip317FeeRule::standard();
+        let payment = vec![Payment::new(recipient, NoteValue::from_raw(50_000), None)];
+
+        let pool = notes(&fvk, &[1_000, 2_000, 70_000, 100_000]);
+
+        // The largest note covers the payment, and the smallest note fills the spare
+        // action that would otherwise be a dummy spend.
+        let plan = select_notes(
+            pool.clone(),
+            payment.clone(),
+            change,
+            None,
+            &rule,
+            Strategy::MinimizeActions,
+        )
+        .unwrap();
+        assert_eq!(values(plan.spends()), vec![1_000, 100_000]);
+        assert_eq!(plan.fee().inner(), 10_000);
+        assert_eq!(plan.change().unwrap().inner(), 41_000);
+        assert_eq!(plan.actions(), 2);
+
+        // The smallest sufficient note is spent on its own.
+        let plan = select_notes(
+            pool.clone(),
+            payment.clone(),
+            change,
+            None,
+            &rule,
+            Strategy::ReduceLinkability,
+        )
+        .unwrap();
+        assert_eq!(values(plan.spends()), vec![70_000]);
+        assert_eq!(plan.change().unwrap().inner(), 10_000);
+
+        // Excess value that cannot pay for a change output goes to the fee.
+        let half = Payment::new(recipient, NoteValue::from_raw(25_000), None);
+        let plan = select_notes(
+            notes(&fvk, &[62_000]),
+            vec![half.clone(), half],
+            change,
+            None,
+            &rule,
+            Strategy::ReduceLinkability,
+        )
+        .unwrap();
+        assert!(plan.change().is_none());
+        assert_eq!(plan.fee().inner(), 12_000);
+
+        assert!(matches!(
+            select_notes(
+                notes(&fvk, &[1_000, 2_000]),
+                payment,
+                change,
+                None,
+                &rule,
+                Strategy::MinimizeActions,
+            ),
+            Err(Error::InsufficientFunds { .. })
+        ));
+    }
+
+    #[test]
+    fn consolidation() {
+        let sk = SpendingKey::from_bytes([7; 32]).unwrap();
+        let fvk = FullViewingKey::from(&sk);
+        let rule = Zip317FeeRule::standard();
+
+        let pool = notes(
+            &fvk,
+            &[20_000, 1_000, 20_000, 3_000, 20_000, 20_000, 20_000],
+        );
+
+        let consolidation = consolidate(pool.clone(), 3, &rule).unwrap();
+        let plans = consolidation.plans();
+        assert_eq!(plans.len(), 2);
+
+        // The first bundle is full, and pays for its third action.
+        assert_eq!(values(plans[0].spends()), vec![20_000; 3]);
+        assert_eq!(plans[0].actions(), 3);
+        assert_eq!(plans[0].fee().inner(), 15_000);
+        assert_eq!(plans[0].change().unwrap().inner(), 45_000);
+        assert!(plans[0].payments().is_empty());
+
+        // The 3_000 note would not pay for the action it adds to the second bundle.
+        assert_eq!(values(plans[1].spends()), vec![20_000; 2]);
+        assert_eq!(plans[1].fee().inner(), 10_000);
+        assert_eq!(plans[1].change().unwrap().inner(), 30_000);
+        assert_eq!(values(consolidation.remaining()), vec![1_000, 3_000]);
+        assert_eq!(consolidation.fee().unwrap().inner(), 25_000);
+
+        assert!(matches!(
+            consolidate(pool, 1, &rule),
+            Err(Error::ActionLimit(1))
+        ));
+    }
+}
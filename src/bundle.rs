@@ -1,8 +1,12 @@
 //! Structs related to bundles of Orchard actions.
 
 pub mod commitments;
+mod effects;
+pub mod history;
+pub mod pruned;
 
 use core::fmt;
+use core::hash::{Hash, Hasher};
 
 use blake2b_simd::Hash as Blake2bHash;
 use memuse::DynamicUsage;
@@ -14,12 +18,13 @@ use crate::{
     address::Address,
     bundle::commitments::{hash_bundle_auth_data, hash_bundle_txid_data},
     circuit::{Instance, Proof, VerifyingKey},
+    fees::FeeRule,
     keys::{IncomingViewingKey, OutgoingViewingKey},
     note::Note,
     note_encryption::OrchardDomain,
     primitives::redpallas::{self, Binding, SpendAuth},
     tree::Anchor,
-    value::{ValueCommitTrapdoor, ValueCommitment, ValueSum},
+    value::{NoteValue, OverflowError, ValueCommitTrapdoor, ValueCommitment, ValueSum},
 };
 
 impl<T> Action<T> {
@@ -185,6 +190,20 @@ impl<T: Authorization, V> Bundle<T, V> {
         &self.actions
     }
 
+    /// Returns the number of ZIP 317 logical actions in this bundle, which is the
+    /// number of its actions.
+    pub fn logical_actions(&self) -> usize {
+        self.actions.len()
+    }
+
+    /// Returns the fee required by `fee_rule` for a transaction containing this bundle.
+    ///
+    /// Every action of the bundle counts as both a spend and an output, whether or not
+    /// it contains a dummy.
+    pub fn fee_required<F: FeeRule>(&self, fee_rule: &F) -> Result<NoteValue, OverflowError> {
+        fee_rule.fee_required(self.actions.len(), self.actions.len())
+    }
+
     /// Returns the Orchard-specific transaction-level flags for this bundle.
     pub fn flags(&self) -> &Flags {
         &self.flags
@@ -428,6 +447,79 @@ impl<V> Bundle<Authorized, V> {
     }
 }
 
+/// Marker for a bundle that only contains its effecting data, without proofs or
+/// signatures.
+///
+/// This is enough to compute [`Bundle::commitment`], and so a transaction's ID, but not
+/// to verify the bundle.
+#[derive(Clone, Copy, Debug)]
+pub struct EffectsOnly;
+
+impl Authorization for EffectsOnly {
+    type SpendAuth = ();
+}
+
+impl<T: Authorization, V> Bundle<T, V> {
+    /// Discards the proof and signatures of this bundle, keeping only its effects.
+    pub fn into_effects_only(self) -> Bundle<EffectsOnly, V> {
+        self.map_authorization(&mut (), |_, _, _| (), |_, _| EffectsOnly)
+    }
+}
+
+impl<V: Copy + Into<i64>> Bundle<Authorized, V> {
+    /// Returns this bundle's contribution to a transaction's `wtxid`: its
+    /// [`Bundle::commitment`] followed by its [`Bundle::authorizing_commitment`].
+    pub fn wtxid_component(&self) -> WtxidComponent {
+        WtxidComponent {
+            txid_digest: self.commitment().into(),
+            auth_digest: self.authorizing_commitment().into(),
+        }
+    }
+
+    /// Passes each field of the canonical encoding of this bundle to `f`, excluding its
+    /// actions, in the order of the v5 transaction format.
+    fn encode_canonical_without_actions(&self, mut f: impl FnMut(&[u8])) {
+        f(&[self.flags.to_byte()]);
+        f(&self.value_balance.into().to_le_bytes());
+        f(&self.anchor.to_bytes());
+        f(self.authorization.proof.as_ref());
+        f(&<[u8; 64]>::from(&self.authorization.binding_signature));
+    }
+}
+
+/// Authorized bundles are equal if their canonical encodings are equal.
+///
+/// The value balance is compared by its `i64` encoding, as in the transaction format.
+impl<V: Copy + Into<i64>> PartialEq for Bundle<Authorized, V> {
+    fn eq(&self, other: &Self) -> bool {
+        let encode = |bundle: &Self| {
+            let mut bytes = vec![];
+            bundle.encode_canonical_without_actions(|field| bytes.extend_from_slice(field));
+            bytes
+        };
+        self.actions.len() == other.actions.len()
+            && self
+                .actions
+                .iter()
+                .zip(other.actions.iter())
+                .all(|(a, b)| a == b)
+            && encode(self) == encode(other)
+    }
+}
+
+impl<V: Copy + Into<i64>> Eq for Bundle<Authorized, V> {}
+
+impl<V: Copy + Into<i64>> Hash for Bundle<Authorized, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // The proof has a variable length, so we prefix the actions with their count.
+        state.write_usize(self.actions.len());
+        for action in self.actions.iter() {
+            action.hash(state);
+        }
+        self.encode_canonical_without_actions(|field| state.write(field));
+    }
+}
+
 impl<V: DynamicUsage> DynamicUsage for Bundle<Authorized, V> {
     fn dynamic_usage(&self) -> usize {
         self.actions.dynamic_usage()
@@ -471,6 +563,46 @@ impl From<BundleCommitment> for [u8; 32] {
 #[derive(Debug)]
 pub struct BundleAuthorizingCommitment(pub Blake2bHash);
 
+impl From<BundleAuthorizingCommitment> for [u8; 32] {
+    fn from(commitment: BundleAuthorizingCommitment) -> Self {
+        // The commitment uses BLAKE2b-256.
+        commitment.0.as_bytes().try_into().unwrap()
+    }
+}
+
+/// The commitments to an authorized bundle that contribute to the `wtxid` of its
+/// transaction, as defined in [ZIP 239: Relay of Version 5 Transactions][zip239].
+///
+/// Two bundles have the same `WtxidComponent` exactly when they have the same effects
+/// and the same authorizing data.
+///
+/// [zip239]: https://zips.z.cash/zip-0239
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WtxidComponent {
+    txid_digest: [u8; 32],
+    auth_digest: [u8; 32],
+}
+
+impl WtxidComponent {
+    /// Returns the commitment to the effects of the bundle.
+    pub fn txid_digest(&self) -> [u8; 32] {
+        self.txid_digest
+    }
+
+    /// Returns the commitment to the authorizing data of the bundle.
+    pub fn auth_digest(&self) -> [u8; 32] {
+        self.auth_digest
+    }
+
+    /// Returns the concatenation of the two commitments.
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0; 64];
+        bytes[..32].copy_from_slice(&self.txid_digest);
+        bytes[32..].copy_from_slice(&self.auth_digest);
+        bytes
+    }
+}
+
 /// Generators for property testing.
 #[cfg(any(test, feature = "test-dependencies"))]
 #[cfg_attr(docsrs, doc(cfg(feature = "test-dependencies")))]
@@ -626,3 +758,64 @@ pub mod testing {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    use proptest::prelude::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{testing::arb_bundle, Authorized, Bundle};
+    use crate::primitives::redpallas::{self, Binding};
+
+    fn hash_of(bundle: &Bundle<Authorized, i64>) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        bundle.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    proptest! {
+        #[test]
+        fn canonical_equality(
+            bundle in arb_bundle(2),
+            other in arb_bundle(2),
+            sk_bytes in prop::array::uniform32(prop::num::u8::ANY),
+        ) {
+            let to_i64 = |b: Bundle<Authorized, _>| b.try_map_value_balance(i64::try_from).unwrap();
+            let bundle = to_i64(bundle);
+            let other = to_i64(other);
+
+            assert_eq!(bundle, bundle.clone());
+            assert_eq!(hash_of(&bundle), hash_of(&bundle.clone()));
+            assert_eq!(bundle.actions().first(), bundle.clone().actions().first());
+            assert_ne!(bundle, other);
+            assert_ne!(bundle.wtxid_component(), other.wtxid_component());
+
+            let wtxid = bundle.wtxid_component();
+            assert_eq!(&wtxid.to_bytes()[..32], bundle.commitment().0.as_bytes());
+            assert_eq!(&wtxid.to_bytes()[32..], bundle.authorizing_commitment().0.as_bytes());
+
+            // Replacing the binding signature changes the bundle, but not its effects.
+            if let Ok(sk) = redpallas::SigningKey::<Binding>::try_from(sk_bytes) {
+                let resigned = bundle.clone().map_authorization(
+                    &mut (),
+                    |_, _, a| a,
+                    |_, auth| {
+                        Authorized::from_parts(
+                            auth.proof().clone(),
+                            sk.sign(StdRng::from_seed([0; 32]), &[0; 32]),
+                        )
+                    },
+                );
+                assert_ne!(bundle, resigned);
+                assert_eq!(
+                    resigned.wtxid_component().txid_digest(),
+                    wtxid.txid_digest()
+                );
+                assert_ne!(resigned.wtxid_component(), wtxid);
+            }
+        }
+    }
+}
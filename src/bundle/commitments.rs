@@ -2,7 +2,13 @@
 
 use blake2b_simd::{Hash as Blake2bHash, Params, State};
 
-use crate::bundle::{Authorization, Authorized, Bundle};
+use crate::{
+    bundle::{pruned::PrunedBundle, Authorization, Authorized, Bundle, Flags},
+    note::{ExtractedNoteCommitment, Nullifier, TransmittedNoteCiphertext},
+    primitives::redpallas::{self, SpendAuth},
+    tree::Anchor,
+    value::ValueCommitment,
+};
 
 const ZCASH_ORCHARD_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdOrchardHash";
 const ZCASH_ORCHARD_ACTIONS_COMPACT_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdOrcActCHash";
@@ -30,31 +36,126 @@ fn hasher(personal: &[u8; 16]) -> State {
 pub(crate) fn hash_bundle_txid_data<A: Authorization, V: Copy + Into<i64>>(
     bundle: &Bundle<A, V>,
 ) -> Blake2bHash {
-    let mut h = hasher(ZCASH_ORCHARD_HASH_PERSONALIZATION);
     let mut ch = hasher(ZCASH_ORCHARD_ACTIONS_COMPACT_HASH_PERSONALIZATION);
-    let mut mh = hasher(ZCASH_ORCHARD_ACTIONS_MEMOS_HASH_PERSONALIZATION);
     let mut nh = hasher(ZCASH_ORCHARD_ACTIONS_NONCOMPACT_HASH_PERSONALIZATION);
 
     for action in bundle.actions().iter() {
-        ch.update(&action.nullifier().to_bytes());
-        ch.update(&action.cmx().to_bytes());
-        ch.update(&action.encrypted_note().epk_bytes);
-        ch.update(&action.encrypted_note().enc_ciphertext[..52]);
+        let encrypted_note = action.encrypted_note();
+        update_compact(
+            &mut ch,
+            action.nullifier(),
+            action.cmx(),
+            &encrypted_note.epk_bytes,
+            &encrypted_note.enc_ciphertext[..52],
+        );
+        update_noncompact(
+            &mut nh,
+            action.cv_net(),
+            action.rk(),
+            &encrypted_note.enc_ciphertext[564..],
+            &encrypted_note.out_ciphertext,
+        );
+    }
 
-        mh.update(&action.encrypted_note().enc_ciphertext[52..564]);
+    finish_txid_data(
+        ch,
+        hash_bundle_memos(bundle.actions().iter().map(|a| a.encrypted_note())),
+        nh,
+        bundle.flags(),
+        (*bundle.value_balance()).into(),
+        bundle.anchor(),
+    )
+}
+
+/// Computes the same commitment as [`hash_bundle_txid_data`] for a pruned bundle, using
+/// its stored digest of the memo ciphertexts.
+pub(crate) fn hash_pruned_bundle_txid_data<A: Authorization, V: Copy + Into<i64>>(
+    bundle: &PrunedBundle<A, V>,
+) -> Blake2bHash {
+    let mut ch = hasher(ZCASH_ORCHARD_ACTIONS_COMPACT_HASH_PERSONALIZATION);
+    let mut nh = hasher(ZCASH_ORCHARD_ACTIONS_NONCOMPACT_HASH_PERSONALIZATION);
 
-        nh.update(&action.cv_net().to_bytes());
-        nh.update(&<[u8; 32]>::from(action.rk()));
-        nh.update(&action.encrypted_note().enc_ciphertext[564..]);
-        nh.update(&action.encrypted_note().out_ciphertext);
+    for action in bundle.actions().iter() {
+        let encrypted_note = action.encrypted_note();
+        update_compact(
+            &mut ch,
+            action.nullifier(),
+            action.cmx(),
+            &encrypted_note.epk_bytes,
+            &encrypted_note.enc_ciphertext_compact,
+        );
+        update_noncompact(
+            &mut nh,
+            action.cv_net(),
+            action.rk(),
+            &encrypted_note.enc_ciphertext_tag,
+            &encrypted_note.out_ciphertext,
+        );
     }
 
+    finish_txid_data(
+        ch,
+        *bundle.memos_digest(),
+        nh,
+        bundle.flags(),
+        (*bundle.value_balance()).into(),
+        bundle.anchor(),
+    )
+}
+
+/// Hashes the memo ciphertexts of a bundle's actions, personalized with
+/// ZCASH_ORCHARD_ACTIONS_MEMOS_HASH_PERSONALIZATION.
+pub(crate) fn hash_bundle_memos<'a>(
+    encrypted_notes: impl Iterator<Item = &'a TransmittedNoteCiphertext>,
+) -> [u8; 32] {
+    let mut mh = hasher(ZCASH_ORCHARD_ACTIONS_MEMOS_HASH_PERSONALIZATION);
+    for encrypted_note in encrypted_notes {
+        mh.update(encrypted_note.memo_chunk());
+    }
+    mh.finalize().as_bytes().try_into().unwrap()
+}
+
+fn update_compact(
+    ch: &mut State,
+    nf: &Nullifier,
+    cmx: &ExtractedNoteCommitment,
+    epk_bytes: &[u8; 32],
+    enc_ciphertext_compact: &[u8],
+) {
+    ch.update(&nf.to_bytes());
+    ch.update(&cmx.to_bytes());
+    ch.update(epk_bytes);
+    ch.update(enc_ciphertext_compact);
+}
+
+fn update_noncompact(
+    nh: &mut State,
+    cv_net: &ValueCommitment,
+    rk: &redpallas::VerificationKey<SpendAuth>,
+    enc_ciphertext_tag: &[u8],
+    out_ciphertext: &[u8; 80],
+) {
+    nh.update(&cv_net.to_bytes());
+    nh.update(&<[u8; 32]>::from(rk));
+    nh.update(enc_ciphertext_tag);
+    nh.update(out_ciphertext);
+}
+
+fn finish_txid_data(
+    ch: State,
+    memos_digest: [u8; 32],
+    nh: State,
+    flags: &Flags,
+    value_balance: i64,
+    anchor: &Anchor,
+) -> Blake2bHash {
+    let mut h = hasher(ZCASH_ORCHARD_HASH_PERSONALIZATION);
     h.update(ch.finalize().as_bytes());
-    h.update(mh.finalize().as_bytes());
+    h.update(&memos_digest);
     h.update(nh.finalize().as_bytes());
-    h.update(&[bundle.flags().to_byte()]);
-    h.update(&(*bundle.value_balance()).into().to_le_bytes());
-    h.update(&bundle.anchor().to_bytes());
+    h.update(&[flags.to_byte()]);
+    h.update(&value_balance.to_le_bytes());
+    h.update(&anchor.to_bytes());
     h.finalize()
 }
 
@@ -72,14 +173,30 @@ pub fn hash_bundle_txid_empty() -> Blake2bHash {
 ///
 /// [zip244]: https://zips.z.cash/zip-0244
 pub(crate) fn hash_bundle_auth_data<V>(bundle: &Bundle<Authorized, V>) -> Blake2bHash {
+    hash_auth_data(
+        bundle.authorization(),
+        bundle.actions().iter().map(|a| a.authorization()),
+    )
+}
+
+/// Computes the same commitment as [`hash_bundle_auth_data`] for a pruned bundle.
+pub(crate) fn hash_pruned_bundle_auth_data<V>(bundle: &PrunedBundle<Authorized, V>) -> Blake2bHash {
+    hash_auth_data(
+        bundle.authorization(),
+        bundle.actions().iter().map(|a| a.authorization()),
+    )
+}
+
+fn hash_auth_data<'a>(
+    authorization: &Authorized,
+    spend_auth_sigs: impl Iterator<Item = &'a redpallas::Signature<SpendAuth>>,
+) -> Blake2bHash {
     let mut h = hasher(ZCASH_ORCHARD_SIGS_HASH_PERSONALIZATION);
-    h.update(bundle.authorization().proof().as_ref());
-    for action in bundle.actions().iter() {
-        h.update(&<[u8; 64]>::from(action.authorization()));
+    h.update(authorization.proof().as_ref());
+    for sig in spend_auth_sigs {
+        h.update(&<[u8; 64]>::from(sig));
     }
-    h.update(&<[u8; 64]>::from(
-        bundle.authorization().binding_signature(),
-    ));
+    h.update(&<[u8; 64]>::from(authorization.binding_signature()));
     h.finalize()
 }
 
@@ -0,0 +1,221 @@
+//! Parsing of the effecting data of serialized bundles.
+
+use std::io::{self, Read};
+
+use nonempty::NonEmpty;
+
+use crate::{
+    action::Action,
+    bundle::{Bundle, EffectsOnly, Flags},
+    note::{ExtractedNoteCommitment, Nullifier, TransmittedNoteCiphertext},
+    primitives::redpallas,
+    tree::Anchor,
+    value::ValueCommitment,
+};
+
+/// The largest CompactSize that Zcash transactions may contain.
+const MAX_COMPACT_SIZE: u64 = 0x0200_0000;
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_owned())
+}
+
+/// Reads a canonically-encoded Bitcoin CompactSize.
+fn read_compact_size<R: Read>(mut reader: R) -> io::Result<u64> {
+    let mut flag = [0u8; 1];
+    reader.read_exact(&mut flag)?;
+    let (size, min) = match flag[0] {
+        0xfd => {
+            let mut bytes = [0u8; 2];
+            reader.read_exact(&mut bytes)?;
+            (u64::from(u16::from_le_bytes(bytes)), 0xfd)
+        }
+        0xfe => {
+            let mut bytes = [0u8; 4];
+            reader.read_exact(&mut bytes)?;
+            (u64::from(u32::from_le_bytes(bytes)), 0x1_0000)
+        }
+        0xff => {
+            let mut bytes = [0u8; 8];
+            reader.read_exact(&mut bytes)?;
+            (u64::from_le_bytes(bytes), 0x1_0000_0000)
+        }
+        size => (u64::from(size), 0),
+    };
+
+    if size < min {
+        Err(invalid("Non-canonical CompactSize"))
+    } else if size > MAX_COMPACT_SIZE {
+        Err(invalid("CompactSize too large"))
+    } else {
+        Ok(size)
+    }
+}
+
+fn read_32<R: Read>(mut reader: R) -> io::Result<[u8; 32]> {
+    let mut bytes = [0u8; 32];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Reads the effecting data of an action, which is its 820-byte `OrchardAction`
+/// encoding.
+fn read_action<R: Read>(mut reader: R) -> io::Result<Action<()>> {
+    let cv_net = Option::from(ValueCommitment::from_bytes(&read_32(&mut reader)?))
+        .ok_or_else(|| invalid("Invalid Orchard cv"))?;
+    let nf = Option::from(Nullifier::from_bytes(&read_32(&mut reader)?))
+        .ok_or_else(|| invalid("Invalid Orchard nullifier"))?;
+    let rk = redpallas::VerificationKey::try_from(read_32(&mut reader)?)
+        .map_err(|_| invalid("Invalid Orchard rk"))?;
+    let cmx = Option::from(ExtractedNoteCommitment::from_bytes(&read_32(&mut reader)?))
+        .ok_or_else(|| invalid("Invalid Orchard cmx"))?;
+    let epk_bytes = read_32(&mut reader)?;
+    let mut enc_ciphertext = [0u8; 580];
+    reader.read_exact(&mut enc_ciphertext)?;
+    let mut out_ciphertext = [0u8; 80];
+    reader.read_exact(&mut out_ciphertext)?;
+
+    Ok(Action::from_parts(
+        nf,
+        rk,
+        cmx,
+        TransmittedNoteCiphertext {
+            epk_bytes,
+            enc_ciphertext,
+            out_ciphertext,
+        },
+        cv_net,
+        (),
+    ))
+}
+
+impl Bundle<EffectsOnly, i64> {
+    /// Reads the effecting data of an Orchard bundle from its encoding in a v5
+    /// transaction, as defined in [ZIP 225][zip225].
+    ///
+    /// This reads `nActionsOrchard`, `vActionsOrchard`, `flagsOrchard`,
+    /// `valueBalanceOrchard` and `anchorOrchard`, and leaves `reader` positioned at
+    /// `sizeProofsOrchard`. The authorizing data that follows is not read. Returns
+    /// `None` if the transaction has no Orchard actions, in which case none of the other
+    /// fields are present.
+    ///
+    /// [zip225]: https://zips.z.cash/zip-0225
+    pub fn read_effects<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        let n_actions = read_compact_size(&mut reader)?;
+        if n_actions == 0 {
+            return Ok(None);
+        }
+
+        // Actions are 820 bytes each, so we don't pre-allocate for an untrusted count.
+        let mut actions = vec![];
+        for _ in 0..n_actions {
+            actions.push(read_action(&mut reader)?);
+        }
+
+        let mut flags = [0u8; 1];
+        reader.read_exact(&mut flags)?;
+        let flags = Flags::from_byte(flags[0]).ok_or_else(|| invalid("Invalid Orchard flags"))?;
+
+        let mut value_balance = [0u8; 8];
+        reader.read_exact(&mut value_balance)?;
+        let value_balance = i64::from_le_bytes(value_balance);
+
+        let anchor = Option::from(Anchor::from_bytes(read_32(&mut reader)?))
+            .ok_or_else(|| invalid("Invalid Orchard anchor"))?;
+
+        Ok(Some(Bundle::from_parts(
+            NonEmpty::from_vec(actions).expect("n_actions > 0"),
+            flags,
+            value_balance,
+            anchor,
+            EffectsOnly,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use proptest::prelude::*;
+
+    use crate::bundle::{testing::arb_bundle, Authorized, Bundle, EffectsOnly};
+
+    fn write_compact_size(size: usize, out: &mut Vec<u8>) {
+        if size < 0xfd {
+            out.push(size as u8);
+        } else {
+            out.push(0xfd);
+            out.extend_from_slice(&(size as u16).to_le_bytes());
+        }
+    }
+
+    /// Encodes a bundle as it appears in a v5 transaction.
+    fn write_v5(bundle: &Bundle<Authorized, i64>) -> Vec<u8> {
+        let mut out = vec![];
+        write_compact_size(bundle.actions().len(), &mut out);
+        for action in bundle.actions().iter() {
+            out.extend_from_slice(&action.cv_net().to_bytes());
+            out.extend_from_slice(&action.nullifier().to_bytes());
+            out.extend_from_slice(&<[u8; 32]>::from(action.rk()));
+            out.extend_from_slice(&action.cmx().to_bytes());
+            out.extend_from_slice(&action.encrypted_note().epk_bytes);
+            out.extend_from_slice(&action.encrypted_note().enc_ciphertext);
+            out.extend_from_slice(&action.encrypted_note().out_ciphertext);
+        }
+        out.push(bundle.flags().to_byte());
+        out.extend_from_slice(&bundle.value_balance().to_le_bytes());
+        out.extend_from_slice(&bundle.anchor().to_bytes());
+        let proof = bundle.authorization().proof().as_ref();
+        write_compact_size(proof.len(), &mut out);
+        out.extend_from_slice(proof);
+        for action in bundle.actions().iter() {
+            out.extend_from_slice(&<[u8; 64]>::from(action.authorization()));
+        }
+        out.extend_from_slice(&<[u8; 64]>::from(
+            bundle.authorization().binding_signature(),
+        ));
+        out
+    }
+
+    proptest! {
+        #[test]
+        fn read_effects(bundle in arb_bundle(2)) {
+            let bundle = bundle.try_map_value_balance(i64::try_from).unwrap();
+            let encoded = write_v5(&bundle);
+            assert_eq!(encoded.len(), 1 + 2 * 820 + 1 + 8 + 32 + 3 + 1973 + 2 * 64 + 64);
+
+            let mut reader = &encoded[..];
+            let effects = Bundle::<EffectsOnly, i64>::read_effects(&mut reader)
+                .unwrap()
+                .unwrap();
+            assert_eq!(effects.commitment().0, bundle.commitment().0);
+            assert_eq!(
+                effects.commitment().0,
+                bundle.clone().into_effects_only().commitment().0
+            );
+
+            // The reader is left at the authorizing data.
+            let mut rest = vec![];
+            reader.read_to_end(&mut rest).unwrap();
+            assert_eq!(rest.len(), 3 + 1973 + 2 * 64 + 64);
+
+            // Truncated encodings are rejected.
+            assert!(Bundle::<EffectsOnly, i64>::read_effects(&encoded[..1 + 820]).is_err());
+        }
+    }
+
+    #[test]
+    fn read_empty_and_malformed() {
+        assert!(Bundle::<EffectsOnly, i64>::read_effects(&[0u8][..])
+            .unwrap()
+            .is_none());
+
+        // CompactSizes must be canonical.
+        assert!(Bundle::<EffectsOnly, i64>::read_effects(&[0xfd, 0x01, 0x00][..]).is_err());
+        assert!(Bundle::<EffectsOnly, i64>::read_effects(
+            &[0xff, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0][..]
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,307 @@
+//! Classification of the actions in a bundle from the point of view of a wallet.
+
+use crate::{
+    address::Address,
+    bundle::{Authorization, Bundle},
+    keys::{FullViewingKey, Scope},
+    note::Note,
+    value::{NoteValue, OverflowError, ValueSum},
+};
+
+/// The effect of an action on the wallet of a [`FullViewingKey`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActionKind {
+    /// The action creates a note sent to one of the wallet's external addresses by
+    /// someone else.
+    Received,
+    /// The action creates a note sent to one of the wallet's internal addresses.
+    Change,
+    /// The action creates a note sent by the wallet to one of its own external
+    /// addresses.
+    ///
+    /// A note is considered to be sent by the wallet if it can be recovered with the
+    /// wallet's outgoing viewing key, or if the bundle spends any of the wallet's notes.
+    SelfTransfer,
+    /// The action creates a note that the wallet sent to another recipient, and which
+    /// was recovered with the wallet's outgoing viewing key.
+    Sent,
+    /// The action spends one of the wallet's notes, and the wallet cannot see the note
+    /// that it creates.
+    Spend,
+    /// Neither the note spent by the action nor the note it creates belong to the
+    /// wallet, or the action only contains dummy notes.
+    Dummy,
+}
+
+/// An action of a bundle, as seen by the wallet of a [`FullViewingKey`].
+#[derive(Clone, Debug)]
+pub struct ActionHistory {
+    index: usize,
+    kind: ActionKind,
+    spent: Option<Note>,
+    output: Option<(Note, Address, [u8; 512])>,
+}
+
+impl ActionHistory {
+    /// Returns the index of the action within its bundle.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns how the action affects the wallet.
+    pub fn kind(&self) -> ActionKind {
+        self.kind
+    }
+
+    /// Returns the wallet's note spent by this action, if any.
+    pub fn spent_note(&self) -> Option<&Note> {
+        self.spent.as_ref()
+    }
+
+    /// Returns the note created by this action, if the wallet could decrypt or recover
+    /// it.
+    pub fn output_note(&self) -> Option<&Note> {
+        self.output.as_ref().map(|(note, _, _)| note)
+    }
+
+    /// Returns the memo of the note created by this action, if the wallet could decrypt
+    /// or recover it.
+    pub fn memo(&self) -> Option<&[u8; 512]> {
+        self.output.as_ref().map(|(_, _, memo)| memo)
+    }
+
+    /// Returns the address of the other party to this action, if it is known.
+    ///
+    /// This is the recipient of a [`ActionKind::Sent`] note. Orchard does not reveal
+    /// who sent a received note, so there is no counterparty for other kinds of action.
+    pub fn counterparty(&self) -> Option<Address> {
+        match (self.kind, &self.output) {
+            (ActionKind::Sent, Some((_, address, _))) => Some(*address),
+            _ => None,
+        }
+    }
+
+    /// Returns the change in the wallet's balance caused by this action: the value of
+    /// the note it creates if that note belongs to the wallet, minus the value of the
+    /// wallet's note that it spends.
+    pub fn net_value(&self) -> ValueSum {
+        let received = match (self.kind, &self.output) {
+            (ActionKind::Received | ActionKind::Change | ActionKind::SelfTransfer, Some(o)) => {
+                o.0.value()
+            }
+            _ => NoteValue::zero(),
+        };
+        let spent = self.spent.map_or_else(NoteValue::zero, |note| note.value());
+        received - spent
+    }
+}
+
+/// The actions of a bundle, as seen by the wallet of a [`FullViewingKey`].
+///
+/// Created by [`Bundle::history`].
+#[derive(Clone, Debug)]
+pub struct BundleHistory {
+    actions: Vec<ActionHistory>,
+}
+
+impl BundleHistory {
+    /// Returns every action of the bundle, in order.
+    pub fn actions(&self) -> &[ActionHistory] {
+        &self.actions
+    }
+
+    /// Returns the actions of the bundle that affect the wallet.
+    pub fn relevant_actions(&self) -> impl Iterator<Item = &ActionHistory> {
+        self.actions
+            .iter()
+            .filter(|action| action.kind != ActionKind::Dummy)
+    }
+
+    /// Returns the change in the wallet's balance caused by the bundle.
+    ///
+    /// This does not include any fee paid by the transaction, which is part of the
+    /// bundle's value balance.
+    pub fn net_value(&self) -> Result<ValueSum, OverflowError> {
+        self.actions.iter().map(|action| action.net_value()).sum()
+    }
+}
+
+impl<T: Authorization, V> Bundle<T, V> {
+    /// Classifies each action in this bundle by its effect on the wallet of `fvk`.
+    ///
+    /// `known_notes` are notes that the wallet has previously received. An action spends
+    /// one of them if its nullifier matches the note's nullifier. Notes created by the
+    /// bundle are trial-decrypted with both of `fvk`'s incoming viewing keys, and
+    /// recovered with both of its outgoing viewing keys.
+    pub fn history(&self, fvk: &FullViewingKey, known_notes: &[Note]) -> BundleHistory {
+        let known_nullifiers: Vec<_> = known_notes
+            .iter()
+            .map(|note| (note.nullifier(fvk), note))
+            .collect();
+        let external_ivk = fvk.to_ivk(Scope::External);
+        let internal_ivk = fvk.to_ivk(Scope::Internal);
+        let ovks = [fvk.to_ovk(Scope::External), fvk.to_ovk(Scope::Internal)];
+
+        let spent: Vec<Option<Note>> = self
+            .actions
+            .iter()
+            .map(|action| {
+                known_nullifiers
+                    .iter()
+                    .find(|(nf, _)| nf == action.nullifier())
+                    .map(|(_, note)| **note)
+            })
+            .collect();
+        let spends_from_wallet = spent.iter().any(Option::is_some);
+
+        let actions = spent
+            .into_iter()
+            .enumerate()
+            .map(|(index, spent)| {
+                let recovered = ovks
+                    .iter()
+                    .find_map(|ovk| self.recover_output_with_ovk(index, ovk));
+                let (kind, output) = if let Some(output) =
+                    self.decrypt_output_with_key(index, &internal_ivk)
+                {
+                    (ActionKind::Change, Some(output))
+                } else if let Some(output) = self.decrypt_output_with_key(index, &external_ivk) {
+                    if recovered.is_some() || spends_from_wallet {
+                        (ActionKind::SelfTransfer, Some(output))
+                    } else {
+                        (ActionKind::Received, Some(output))
+                    }
+                } else if recovered.is_some() {
+                    (ActionKind::Sent, recovered)
+                } else if spent.is_some() {
+                    (ActionKind::Spend, None)
+                } else {
+                    (ActionKind::Dummy, None)
+                };
+
+                ActionHistory {
+                    index,
+                    kind,
+                    spent,
+                    output,
+                }
+            })
+            .collect();
+
+        BundleHistory { actions }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::OsRng;
+
+    use super::ActionKind;
+    use crate::{
+        builder::Builder,
+        bundle::{Bundle, Flags},
+        constants::MERKLE_DEPTH_ORCHARD,
+        keys::{FullViewingKey, Scope, SpendingKey},
+        tree::{MerklePath, EMPTY_ROOTS},
+        value::NoteValue,
+    };
+
+    fn kinds(mut kinds: Vec<ActionKind>) -> Vec<ActionKind> {
+        kinds.sort_by_key(|kind| *kind as u8);
+        kinds
+    }
+
+    #[test]
+    fn bundle_history() {
+        let mut rng = OsRng;
+
+        let fvk = FullViewingKey::from(&SpendingKey::random(&mut rng));
+        let external = fvk.address_at(0u32, Scope::External);
+        let internal = fvk.address_at(0u32, Scope::Internal);
+        let other =
+            FullViewingKey::from(&SpendingKey::random(&mut rng)).address_at(0u32, Scope::External);
+
+        // Someone else sends us a note.
+        let mut builder = Builder::new(
+            Flags::from_parts(true, true),
+            EMPTY_ROOTS[MERKLE_DEPTH_ORCHARD].into(),
+        );
+        builder
+            .add_recipient(None, external, NoteValue::from_raw(30_000), Some([1; 512]))
+            .unwrap();
+        let bundle: Bundle<_, i64> = builder.build(&mut rng).unwrap();
+
+        let history = bundle.history(&fvk, &[]);
+        let received: Vec<_> = history.relevant_actions().collect();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].kind(), ActionKind::Received);
+        assert_eq!(received[0].memo(), Some(&[1; 512]));
+        assert_eq!(received[0].counterparty(), None);
+        assert_eq!(i64::try_from(history.net_value().unwrap()).unwrap(), 30_000);
+        let note = *received[0].output_note().unwrap();
+
+        // We spend it, paying someone else and ourselves, and taking change.
+        let merkle_path = MerklePath::dummy(&mut rng);
+        let mut builder = Builder::new(
+            Flags::from_parts(true, true),
+            merkle_path.root(note.commitment().into()),
+        );
+        builder.add_spend(fvk.clone(), note, merkle_path).unwrap();
+        builder
+            .add_recipient(
+                Some(fvk.to_ovk(Scope::External)),
+                other,
+                NoteValue::from_raw(10_000),
+                None,
+            )
+            .unwrap();
+        builder
+            .add_recipient(None, external, NoteValue::from_raw(5_000), None)
+            .unwrap();
+        builder
+            .add_recipient(
+                Some(fvk.to_ovk(Scope::Internal)),
+                internal,
+                NoteValue::from_raw(15_000),
+                None,
+            )
+            .unwrap();
+        let bundle: Bundle<_, i64> = builder.build(&mut rng).unwrap();
+
+        let history = bundle.history(&fvk, &[note]);
+        assert_eq!(
+            kinds(history.actions().iter().map(|a| a.kind()).collect()),
+            vec![
+                ActionKind::Change,
+                ActionKind::SelfTransfer,
+                ActionKind::Sent
+            ],
+        );
+        let sent = history
+            .actions()
+            .iter()
+            .find(|a| a.kind() == ActionKind::Sent)
+            .unwrap();
+        assert_eq!(sent.counterparty(), Some(other));
+        assert_eq!(
+            history
+                .actions()
+                .iter()
+                .filter(|a| a.spent_note().is_some())
+                .count(),
+            1
+        );
+        assert_eq!(
+            i64::try_from(history.net_value().unwrap()).unwrap(),
+            -10_000
+        );
+
+        // Another wallet sees nothing.
+        let stranger = FullViewingKey::from(&SpendingKey::random(&mut rng));
+        let history = bundle.history(&stranger, &[]);
+        assert!(history
+            .actions()
+            .iter()
+            .all(|a| a.kind() == ActionKind::Dummy));
+    }
+}
@@ -0,0 +1,175 @@
+//! Bundles whose memos have been discarded.
+//!
+//! ZIP 244 commits to the memo ciphertexts of a bundle separately from the rest of its
+//! actions, so a bundle can be stored without them as long as the digest of its memos is
+//! kept. This saves 512 bytes per action, while still allowing the bundle's commitments
+//! (and so its transaction's ID) to be recomputed.
+
+use core::fmt;
+
+use nonempty::NonEmpty;
+
+use crate::{
+    bundle::{
+        commitments::{
+            hash_bundle_memos, hash_pruned_bundle_auth_data, hash_pruned_bundle_txid_data,
+        },
+        Authorization, Authorized, Bundle, BundleAuthorizingCommitment, BundleCommitment, Flags,
+    },
+    tree::Anchor,
+};
+
+pub use crate::action::PrunedAction;
+
+/// A [`Bundle`] whose actions have been pruned of their memos.
+///
+/// Created by [`Bundle::prune`].
+#[derive(Clone)]
+pub struct PrunedBundle<T: Authorization, V> {
+    actions: NonEmpty<PrunedAction<T::SpendAuth>>,
+    flags: Flags,
+    value_balance: V,
+    anchor: Anchor,
+    authorization: T,
+    memos_digest: [u8; 32],
+}
+
+impl<T: Authorization, V: fmt::Debug> fmt::Debug for PrunedBundle<T, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrunedBundle")
+            .field("actions", &self.actions.iter().collect::<Vec<_>>())
+            .field("flags", &self.flags)
+            .field("value_balance", &self.value_balance)
+            .field("anchor", &self.anchor)
+            .field("authorization", &self.authorization)
+            .field("memos_digest", &hex::encode(self.memos_digest))
+            .finish()
+    }
+}
+
+impl<T: Authorization, V> PrunedBundle<T, V> {
+    /// Constructs a `PrunedBundle` from its constituent parts.
+    ///
+    /// `memos_digest` must be the BLAKE2b-256 hash of the memo ciphertexts of the
+    /// original bundle, personalized with `ZTxIdOrcActMHash`, as defined in
+    /// [ZIP 244][zip244]. It is not possible to check this, but the bundle's
+    /// commitments will not match those of the original bundle if it is wrong.
+    ///
+    /// [zip244]: https://zips.z.cash/zip-0244
+    pub fn from_parts(
+        actions: NonEmpty<PrunedAction<T::SpendAuth>>,
+        flags: Flags,
+        value_balance: V,
+        anchor: Anchor,
+        authorization: T,
+        memos_digest: [u8; 32],
+    ) -> Self {
+        PrunedBundle {
+            actions,
+            flags,
+            value_balance,
+            anchor,
+            authorization,
+            memos_digest,
+        }
+    }
+
+    /// Returns the list of actions that make up this bundle.
+    pub fn actions(&self) -> &NonEmpty<PrunedAction<T::SpendAuth>> {
+        &self.actions
+    }
+
+    /// Returns the Orchard-specific transaction-level flags for this bundle.
+    pub fn flags(&self) -> &Flags {
+        &self.flags
+    }
+
+    /// Returns the net value moved into or out of the Orchard shielded pool.
+    pub fn value_balance(&self) -> &V {
+        &self.value_balance
+    }
+
+    /// Returns the root of the Orchard commitment tree that this bundle commits to.
+    pub fn anchor(&self) -> &Anchor {
+        &self.anchor
+    }
+
+    /// Returns the authorization for this bundle.
+    pub fn authorization(&self) -> &T {
+        &self.authorization
+    }
+
+    /// Returns the ZIP 244 digest of the memo ciphertexts that were pruned from this
+    /// bundle.
+    pub fn memos_digest(&self) -> &[u8; 32] {
+        &self.memos_digest
+    }
+}
+
+impl<T: Authorization, V: Copy + Into<i64>> PrunedBundle<T, V> {
+    /// Computes a commitment to the effects of this bundle, which is equal to
+    /// [`Bundle::commitment`] for the bundle it was pruned from.
+    pub fn commitment(&self) -> BundleCommitment {
+        BundleCommitment(hash_pruned_bundle_txid_data(self))
+    }
+}
+
+impl<V> PrunedBundle<Authorized, V> {
+    /// Computes a commitment to the authorizing data within this bundle, which is equal
+    /// to [`Bundle::authorizing_commitment`] for the bundle it was pruned from.
+    pub fn authorizing_commitment(&self) -> BundleAuthorizingCommitment {
+        BundleAuthorizingCommitment(hash_pruned_bundle_auth_data(self))
+    }
+}
+
+impl<T: Authorization, V> Bundle<T, V> {
+    /// Discards the memo ciphertexts of this bundle's actions, keeping their digest.
+    ///
+    /// Notes can no longer be fully decrypted from the pruned bundle, but its
+    /// commitments can still be computed.
+    pub fn prune(self) -> PrunedBundle<T, V> {
+        let memos_digest = hash_bundle_memos(self.actions.iter().map(|a| a.encrypted_note()));
+        PrunedBundle {
+            actions: self.actions.map(PrunedAction::from),
+            flags: self.flags,
+            value_balance: self.value_balance,
+            anchor: self.anchor,
+            authorization: self.authorization,
+            memos_digest,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::bundle::testing::arb_bundle;
+
+    proptest! {
+        #[test]
+        fn pruned_commitments(bundle in arb_bundle(3)) {
+            let bundle = bundle.try_map_value_balance(i64::try_from).unwrap();
+            let commitment = bundle.commitment().0;
+            let authorizing_commitment = bundle.authorizing_commitment().0;
+
+            let pruned = bundle.prune();
+            assert_eq!(pruned.actions().len(), 3);
+            assert_eq!(pruned.commitment().0, commitment);
+            assert_eq!(pruned.authorizing_commitment().0, authorizing_commitment);
+
+            // The memos digest is committed to.
+            let mut memos_digest = *pruned.memos_digest();
+            memos_digest[0] ^= 1;
+            let tampered = super::PrunedBundle::from_parts(
+                pruned.actions().clone(),
+                *pruned.flags(),
+                *pruned.value_balance(),
+                *pruned.anchor(),
+                pruned.authorization().clone(),
+                memos_digest,
+            );
+            assert_ne!(tampered.commitment().0, commitment);
+        }
+    }
+}
@@ -0,0 +1,422 @@
+//! An in-memory model of Orchard chain state, for use in tests.
+//!
+//! [`Chain`] applies authorized bundles block by block, enforcing the subset of the
+//! Orchard consensus rules that depend on shared chain state: every bundle that enables
+//! spends must use a known anchor, no nullifier may be revealed twice, and the Orchard
+//! pool balance may not become negative. It maintains
+//! the note commitment tree alongside, so that wallet-side tests can obtain Merkle
+//! paths for notes they have received.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use incrementalmerkletree::{bridgetree::BridgeTree, Hashable, Position, Tree};
+
+use crate::{
+    bundle::{Authorized, Bundle},
+    circuit::VerifyingKey,
+    constants::MERKLE_DEPTH_ORCHARD,
+    note::{ExtractedNoteCommitment, Nullifier},
+    tree::{Anchor, AnchorSet, AnchorWindow, MerkleHashOrchard, MerklePath},
+    value::{NoteValue, TurnstileError, ValuePool},
+};
+
+/// Errors that can occur when applying a block to a [`Chain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A bundle with spends enabled referred to an anchor that is not the root of the
+    /// note commitment tree at the end of any previously-applied block.
+    UnknownAnchor {
+        /// The index of the offending bundle within the block.
+        bundle: usize,
+    },
+    /// A nullifier was revealed that has already been revealed, either earlier in the
+    /// chain or earlier in the same block.
+    DoubleSpend {
+        /// The index of the offending bundle within the block.
+        bundle: usize,
+        /// The repeated nullifier.
+        nullifier: Nullifier,
+    },
+    /// The proof for a bundle did not verify.
+    InvalidProof {
+        /// The index of the offending bundle within the block.
+        bundle: usize,
+    },
+    /// A spend authorization signature or the binding signature of a bundle did not
+    /// verify.
+    InvalidSignature {
+        /// The index of the offending bundle within the block.
+        bundle: usize,
+    },
+    /// The block would violate the Orchard value pool turnstile.
+    Turnstile(TurnstileError),
+    /// The note commitment tree is full.
+    TreeFull,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownAnchor { bundle } => {
+                write!(f, "Bundle {} uses an unknown anchor", bundle)
+            }
+            Error::DoubleSpend { bundle, nullifier } => write!(
+                f,
+                "Bundle {} reveals nullifier {} which was already revealed",
+                bundle,
+                hex::encode(nullifier.to_bytes())
+            ),
+            Error::InvalidProof { bundle } => write!(f, "Bundle {} has an invalid proof", bundle),
+            Error::InvalidSignature { bundle } => {
+                write!(f, "Bundle {} has an invalid signature", bundle)
+            }
+            Error::Turnstile(e) => write!(f, "Block rejected by turnstile: {}", e),
+            Error::TreeFull => write!(f, "Note commitment tree is full"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// An in-memory Orchard chain.
+///
+/// Blocks are applied atomically with [`Chain::apply_block`]: if any bundle in the block
+/// is rejected, the chain state is left unchanged.
+///
+/// Signatures are checked against [`Bundle::commitment`] as the sighash, which is what a
+/// transaction containing only an Orchard bundle would use. Proofs are only checked if
+/// the chain was constructed with [`Chain::with_verifying_key`].
+pub struct Chain {
+    vk: Option<VerifyingKey>,
+    tree: BridgeTree<MerkleHashOrchard, MERKLE_DEPTH_ORCHARD_U8>,
+    positions: BTreeMap<[u8; 32], Position>,
+    anchors: AnchorWindow,
+    nullifiers: BTreeSet<Nullifier>,
+    pool: ValuePool,
+    height: u32,
+}
+
+const MERKLE_DEPTH_ORCHARD_U8: u8 = MERKLE_DEPTH_ORCHARD as u8;
+
+impl fmt::Debug for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Chain")
+            .field("verifies_proofs", &self.vk.is_some())
+            .field("height", &self.height)
+            .field("anchor", &self.anchor())
+            .field("notes", &self.positions.len())
+            .field("nullifiers", &self.nullifiers.len())
+            .field("pool_balance", &self.pool.balance())
+            .finish()
+    }
+}
+
+impl Default for Chain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Chain {
+    /// Constructs an empty chain that does not verify proofs.
+    ///
+    /// The root of the empty note commitment tree is a valid anchor.
+    pub fn new() -> Self {
+        Self::with_anchor_window(usize::MAX)
+    }
+
+    /// Constructs an empty chain that does not verify proofs, and only accepts the
+    /// anchors of the most recent `blocks` blocks (counting the empty tree before the
+    /// first block).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `blocks` is zero.
+    pub fn with_anchor_window(blocks: usize) -> Self {
+        let mut anchors = AnchorWindow::new(blocks);
+        anchors.record(
+            0,
+            MerkleHashOrchard::empty_root(MERKLE_DEPTH_ORCHARD_U8.into()).into(),
+        );
+        Chain {
+            vk: None,
+            tree: BridgeTree::new(0),
+            positions: BTreeMap::new(),
+            anchors,
+            nullifiers: BTreeSet::new(),
+            pool: ValuePool::default(),
+            height: 0,
+        }
+    }
+
+    /// Constructs an empty chain that verifies the proof of every bundle it applies.
+    pub fn with_verifying_key(vk: VerifyingKey) -> Self {
+        Chain {
+            vk: Some(vk),
+            ..Self::new()
+        }
+    }
+
+    /// Returns the number of blocks that have been applied to this chain.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the current root of the note commitment tree.
+    pub fn anchor(&self) -> Anchor {
+        self.anchors
+            .latest()
+            .expect("always contains the current root")
+            .1
+    }
+
+    /// Returns whether `anchor` is the root of the note commitment tree at the end of
+    /// any block in the anchor window (including the empty tree before the first block).
+    pub fn is_valid_anchor(&self, anchor: &Anchor) -> bool {
+        self.anchors.contains(anchor)
+    }
+
+    /// Returns the total value held in the Orchard pool.
+    pub fn pool_balance(&self) -> NoteValue {
+        self.pool.balance()
+    }
+
+    /// Returns whether `nullifier` has been revealed on this chain.
+    pub fn is_spent(&self, nullifier: &Nullifier) -> bool {
+        self.nullifiers.contains(nullifier)
+    }
+
+    /// Returns the Merkle path from the note commitment `cmx` to the current anchor,
+    /// or `None` if `cmx` has not been added to the tree.
+    pub fn merkle_path(&self, cmx: &ExtractedNoteCommitment) -> Option<MerklePath> {
+        let position = *self.positions.get(&cmx.to_bytes())?;
+        let root = self.tree.root(0)?;
+        let auth_path = self.tree.authentication_path(position, &root)?;
+        Some((position, auth_path).into())
+    }
+
+    /// Validates every bundle in `block` against the current chain state and, if all
+    /// are valid, applies them.
+    ///
+    /// Returns the anchor at the end of the block.
+    pub fn apply_block<V: Copy + Into<i64>>(
+        &mut self,
+        block: &[Bundle<Authorized, V>],
+    ) -> Result<Anchor, Error> {
+        let mut block_nullifiers = BTreeSet::new();
+        for (i, bundle) in block.iter().enumerate() {
+            self.check_bundle(i, bundle)?;
+            for action in bundle.actions() {
+                let nf = *action.nullifier();
+                if self.nullifiers.contains(&nf) || !block_nullifiers.insert(nf) {
+                    return Err(Error::DoubleSpend {
+                        bundle: i,
+                        nullifier: nf,
+                    });
+                }
+            }
+        }
+
+        let new_leaves = block.iter().map(|b| b.actions().len()).sum::<usize>();
+        let next_position = self.tree.current_position().map_or(0, |p| u64::from(p) + 1);
+        if next_position + new_leaves as u64 > 1 << MERKLE_DEPTH_ORCHARD {
+            return Err(Error::TreeFull);
+        }
+
+        let mut pool = self.pool;
+        pool.apply_block(block).map_err(Error::Turnstile)?;
+
+        for action in block.iter().flat_map(|b| b.actions().iter()) {
+            let cmx = action.cmx();
+            assert!(self.tree.append(&MerkleHashOrchard::from_cmx(cmx)));
+            // Witness every leaf, so that tests can request a path for any note.
+            let position = self.tree.witness().expect("a leaf was just appended");
+            self.positions.insert(cmx.to_bytes(), position);
+        }
+        self.nullifiers.append(&mut block_nullifiers);
+        self.pool = pool;
+        self.height += 1;
+
+        let anchor = self
+            .tree
+            .root(0)
+            .expect("the current root is always available")
+            .into();
+        self.anchors.record(self.height, anchor);
+        Ok(anchor)
+    }
+
+    /// Checks the rules that apply to a single bundle in isolation from the rest of its
+    /// block.
+    fn check_bundle<V: Copy + Into<i64>>(
+        &self,
+        i: usize,
+        bundle: &Bundle<Authorized, V>,
+    ) -> Result<(), Error> {
+        if bundle.flags().spends_enabled() && !self.is_valid_anchor(bundle.anchor()) {
+            return Err(Error::UnknownAnchor { bundle: i });
+        }
+
+        if let Some(vk) = &self.vk {
+            bundle
+                .verify_proof(vk)
+                .map_err(|_| Error::InvalidProof { bundle: i })?;
+        }
+
+        let sighash: [u8; 32] = bundle.commitment().into();
+        let spend_auth_valid = bundle
+            .actions()
+            .iter()
+            .all(|action| action.rk().verify(&sighash, action.authorization()).is_ok());
+        let binding_valid = bundle
+            .binding_validating_key()
+            .verify(&sighash, bundle.authorization().binding_signature())
+            .is_ok();
+        if spend_auth_valid && binding_valid {
+            Ok(())
+        } else {
+            Err(Error::InvalidSignature { bundle: i })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::OsRng;
+
+    use super::{Chain, Error};
+    use crate::{
+        builder::Builder,
+        bundle::{Authorized, Flags},
+        circuit::{ProvingKey, VerifyingKey},
+        keys::{FullViewingKey, Scope, SpendAuthorizingKey, SpendingKey},
+        note::ExtractedNoteCommitment,
+        tree::Anchor,
+        value::NoteValue,
+        Bundle,
+    };
+
+    fn build_bundle(
+        pk: &ProvingKey,
+        builder: Builder,
+        saks: &[SpendAuthorizingKey],
+    ) -> Bundle<Authorized, i64> {
+        let mut rng = OsRng;
+        let unauthorized = builder.build(&mut rng).unwrap();
+        let sighash = unauthorized.commitment().into();
+        let proven = unauthorized.create_proof(pk, &mut rng).unwrap();
+        proven.apply_signatures(&mut rng, sighash, saks).unwrap()
+    }
+
+    #[test]
+    fn spends_and_double_spends() {
+        let pk = ProvingKey::build();
+        let mut chain = Chain::with_verifying_key(VerifyingKey::build());
+
+        let sk = SpendingKey::from_bytes([7; 32]).unwrap();
+        let fvk = FullViewingKey::from(&sk);
+        let recipient = fvk.address_at(0u32, Scope::External);
+
+        // Shield some funds.
+        let mut builder = Builder::new(Flags::from_parts(false, true), chain.anchor());
+        builder
+            .add_recipient(None, recipient, NoteValue::from_raw(5000), None)
+            .unwrap();
+        let shielding = build_bundle(&pk, builder, &[]);
+        chain.apply_block(&[shielding.clone()]).unwrap();
+        assert_eq!(chain.height(), 1);
+        assert_eq!(chain.pool_balance().inner(), 5000);
+
+        let (_, _, note, _, _) = shielding
+            .decrypt_outputs_with_keys(&[fvk.to_ivk(Scope::External)])
+            .pop()
+            .unwrap();
+        let cmx: ExtractedNoteCommitment = note.commitment().into();
+        let merkle_path = chain.merkle_path(&cmx).unwrap();
+        assert_eq!(merkle_path.root(cmx), chain.anchor());
+
+        // Spend the shielded note.
+        let mut builder = Builder::new(Flags::from_parts(true, true), chain.anchor());
+        builder.add_spend(fvk.clone(), note, merkle_path).unwrap();
+        builder
+            .add_recipient(None, recipient, NoteValue::from_raw(5000), None)
+            .unwrap();
+        let spend = build_bundle(&pk, builder, &[SpendAuthorizingKey::from(&sk)]);
+
+        // The same spend twice within one block is rejected, and leaves the chain
+        // unchanged.
+        let anchor = chain.anchor();
+        assert!(matches!(
+            chain.apply_block(&[spend.clone(), spend.clone()]),
+            Err(Error::DoubleSpend { bundle: 1, .. })
+        ));
+        assert_eq!(chain.height(), 1);
+        assert_eq!(chain.anchor(), anchor);
+
+        chain.apply_block(&[spend.clone()]).unwrap();
+        assert!(chain.is_spent(&note.nullifier(&fvk)));
+        assert_eq!(chain.pool_balance().inner(), 5000);
+
+        // Replaying the spend in a later block is rejected.
+        assert!(matches!(
+            chain.apply_block(&[spend]),
+            Err(Error::DoubleSpend { bundle: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn unknown_anchor() {
+        let pk = ProvingKey::build();
+        let mut chain = Chain::new();
+
+        let sk = SpendingKey::from_bytes([7; 32]).unwrap();
+        let fvk = FullViewingKey::from(&sk);
+        let recipient = fvk.address_at(0u32, Scope::External);
+
+        // A bundle with spends enabled must use a known anchor, even if it only
+        // contains dummy spends.
+        let anchor = Anchor::from_bytes([1; 32]).unwrap();
+        let mut builder = Builder::new(Flags::from_parts(true, true), anchor);
+        builder
+            .add_recipient(None, recipient, NoteValue::from_raw(0), None)
+            .unwrap();
+        let bundle = build_bundle(&pk, builder, &[]);
+
+        assert_eq!(
+            chain.apply_block(&[bundle]),
+            Err(Error::UnknownAnchor { bundle: 0 })
+        );
+        assert_eq!(chain.height(), 0);
+    }
+
+    #[test]
+    fn anchor_window() {
+        let pk = ProvingKey::fake();
+        let mut chain = Chain::with_anchor_window(1);
+        let empty_root = chain.anchor();
+
+        let sk = SpendingKey::from_bytes([7; 32]).unwrap();
+        let fvk = FullViewingKey::from(&sk);
+        let recipient = fvk.address_at(0u32, Scope::External);
+
+        let mut builder = Builder::new(Flags::from_parts(false, true), empty_root);
+        builder
+            .add_recipient(None, recipient, NoteValue::from_raw(5000), None)
+            .unwrap();
+        chain
+            .apply_block(&[build_bundle(&pk, builder, &[])])
+            .unwrap();
+
+        // The empty root has left the window.
+        assert!(!chain.is_valid_anchor(&empty_root));
+        let mut builder = Builder::new(Flags::from_parts(true, true), empty_root);
+        builder
+            .add_recipient(None, recipient, NoteValue::from_raw(0), None)
+            .unwrap();
+        assert_eq!(
+            chain.apply_block(&[build_bundle(&pk, builder, &[])]),
+            Err(Error::UnknownAnchor { bundle: 0 })
+        );
+    }
+}
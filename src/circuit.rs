@@ -6,7 +6,7 @@ use group::{Curve, GroupEncoding};
 use halo2_proofs::{
     circuit::{floor_planner, Layouter},
     plonk::{
-        self, Advice, Column, Constraints, Expression, Instance as InstanceColumn, Selector,
+        self, Advice, Column, Constraints, Expression, Fixed, Instance as InstanceColumn, Selector,
         SingleVerifier,
     },
     poly::Rotation,
@@ -15,6 +15,7 @@ use halo2_proofs::{
 use memuse::DynamicUsage;
 use pasta_curves::{arithmetic::CurveAffine, pallas, vesta};
 use rand::RngCore;
+use zeroize::Zeroize;
 
 use self::{
     commit_ivk::{CommitIvkChip, CommitIvkConfig},
@@ -38,7 +39,7 @@ use crate::{
         ExtractedNoteCommitment,
     },
     primitives::redpallas::{SpendAuth, VerificationKey},
-    spec::NonIdentityPallasPoint,
+    spec::{NonIdentityPallasPoint, SecretField},
     tree::{Anchor, MerkleHashOrchard},
     value::{NoteValue, ValueCommitTrapdoor, ValueCommitment},
 };
@@ -59,8 +60,18 @@ use halo2_gadgets::{
 };
 
 mod commit_ivk;
+mod diagnostics;
+#[cfg(any(test, feature = "test-dependencies"))]
+mod fake;
 pub mod gadget;
 mod note_commit;
+mod report;
+mod request;
+pub mod reserves;
+
+pub use diagnostics::{Check, WitnessFailure};
+pub use report::{CircuitReport, RegionCategory};
+pub use request::ProvingRequest;
 
 /// Size of the Orchard circuit.
 const K: u32 = 11;
@@ -76,10 +87,23 @@ const CMX: usize = 6;
 const ENABLE_SPEND: usize = 7;
 const ENABLE_OUTPUT: usize = 8;
 
+// Index and name of the gate enforcing the Orchard-specific checks, and the names of
+// its constraints in the order they are created. halo2 numbers gates in the order they
+// are created, and this is the first gate created in `Circuit::configure`.
+const ORCHARD_CHECKS_GATE_INDEX: usize = 0;
+const ORCHARD_CHECKS_GATE: &str = "Orchard circuit checks";
+const ORCHARD_CHECKS_CONSTRAINTS: [&str; 4] = [
+    "v_old - v_new = magnitude * sign",
+    "Either v_old = 0, or root = anchor",
+    "v_old = 0 or enable_spends = 1",
+    "v_new = 0 or enable_outputs = 1",
+];
+
 /// Configuration needed to use the Orchard Action circuit.
 #[derive(Clone, Debug)]
 pub struct Config {
     primary: Column<InstanceColumn>,
+    constants: Column<Fixed>,
     q_orchard: Selector,
     advices: [Column<Advice>; 10],
     add_config: AddConfig,
@@ -97,6 +121,8 @@ pub struct Config {
 }
 
 /// The Orchard Action circuit.
+///
+/// The secret randomness in its witnesses is zeroized when it is dropped.
 #[derive(Clone, Debug, Default)]
 pub struct Circuit {
     pub(crate) path: Option<[MerkleHashOrchard; MERKLE_DEPTH_ORCHARD]>,
@@ -105,21 +131,30 @@ pub struct Circuit {
     pub(crate) pk_d_old: Option<DiversifiedTransmissionKey>,
     pub(crate) v_old: Option<NoteValue>,
     pub(crate) rho_old: Option<Nullifier>,
-    pub(crate) psi_old: Option<pallas::Base>,
+    pub(crate) psi_old: Option<SecretField<pallas::Base>>,
     pub(crate) rcm_old: Option<NoteCommitTrapdoor>,
     pub(crate) cm_old: Option<NoteCommitment>,
-    pub(crate) alpha: Option<pallas::Scalar>,
+    pub(crate) alpha: Option<SecretField<pallas::Scalar>>,
     pub(crate) ak: Option<SpendValidatingKey>,
     pub(crate) nk: Option<NullifierDerivingKey>,
     pub(crate) rivk: Option<CommitIvkRandomness>,
     pub(crate) g_d_new: Option<NonIdentityPallasPoint>,
     pub(crate) pk_d_new: Option<DiversifiedTransmissionKey>,
     pub(crate) v_new: Option<NoteValue>,
-    pub(crate) psi_new: Option<pallas::Base>,
+    pub(crate) psi_new: Option<SecretField<pallas::Base>>,
     pub(crate) rcm_new: Option<NoteCommitTrapdoor>,
     pub(crate) rcv: Option<ValueCommitTrapdoor>,
 }
 
+impl Drop for Circuit {
+    fn drop(&mut self) {
+        // The keys and trapdoors zeroize themselves.
+        self.psi_old.zeroize();
+        self.alpha.zeroize();
+        self.psi_new.zeroize();
+    }
+}
+
 impl plonk::Circuit<pallas::Base> for Circuit {
     type Config = Config;
     type FloorPlanner = floor_planner::V1;
@@ -147,8 +182,9 @@ impl plonk::Circuit<pallas::Base> for Circuit {
         // Either v_old = 0, or calculated root = anchor
         // Constrain v_old = 0 or enable_spends = 1.
         // Constrain v_new = 0 or enable_outputs = 1.
+        // This must remain the first gate, as `ORCHARD_CHECKS_GATE_INDEX` relies on it.
         let q_orchard = meta.selector();
-        meta.create_gate("Orchard circuit checks", |meta| {
+        meta.create_gate(ORCHARD_CHECKS_GATE, |meta| {
             let q_orchard = meta.query_selector(q_orchard);
             let v_old = meta.query_advice(advices[0], Rotation::cur());
             let v_new = meta.query_advice(advices[1], Rotation::cur());
@@ -167,19 +203,19 @@ impl plonk::Circuit<pallas::Base> for Circuit {
                 q_orchard,
                 [
                     (
-                        "v_old - v_new = magnitude * sign",
+                        ORCHARD_CHECKS_CONSTRAINTS[0],
                         v_old.clone() - v_new.clone() - magnitude * sign,
                     ),
                     (
-                        "Either v_old = 0, or root = anchor",
+                        ORCHARD_CHECKS_CONSTRAINTS[1],
                         v_old.clone() * (root - anchor),
                     ),
                     (
-                        "v_old = 0 or enable_spends = 1",
+                        ORCHARD_CHECKS_CONSTRAINTS[2],
                         v_old * (one.clone() - enable_spends),
                     ),
                     (
-                        "v_new = 0 or enable_outputs = 1",
+                        ORCHARD_CHECKS_CONSTRAINTS[3],
                         v_new * (one - enable_outputs),
                     ),
                 ],
@@ -300,6 +336,7 @@ impl plonk::Circuit<pallas::Base> for Circuit {
 
         Config {
             primary,
+            constants: lagrange_coeffs[0],
             q_orchard,
             advices,
             add_config,
@@ -333,7 +370,7 @@ impl plonk::Circuit<pallas::Base> for Circuit {
             let psi_old = assign_free_advice(
                 layouter.namespace(|| "witness psi_old"),
                 config.advices[0],
-                self.psi_old,
+                self.psi_old.as_ref().map(|psi_old| **psi_old),
             )?;
 
             // Witness rho_old
@@ -369,7 +406,7 @@ impl plonk::Circuit<pallas::Base> for Circuit {
             let nk = assign_free_advice(
                 layouter.namespace(|| "witness nk"),
                 config.advices[0],
-                self.nk.map(|nk| nk.inner()),
+                self.nk.as_ref().map(|nk| nk.inner()),
             )?;
 
             // Witness v_old.
@@ -483,8 +520,11 @@ impl plonk::Circuit<pallas::Base> for Circuit {
 
         // Spend authority
         {
-            let alpha =
-                ScalarFixed::new(ecc_chip.clone(), layouter.namespace(|| "alpha"), self.alpha)?;
+            let alpha = ScalarFixed::new(
+                ecc_chip.clone(),
+                layouter.namespace(|| "alpha"),
+                self.alpha.as_ref().map(|alpha| **alpha),
+            )?;
 
             // alpha_commitment = [alpha] SpendAuthG
             let (alpha_commitment, _) = {
@@ -508,7 +548,7 @@ impl plonk::Circuit<pallas::Base> for Circuit {
                 let rivk = ScalarFixed::new(
                     ecc_chip.clone(),
                     layouter.namespace(|| "rcv"),
-                    self.rivk.map(|rivk| rivk.inner()),
+                    self.rivk.as_ref().map(|rivk| rivk.inner()),
                 )?;
 
                 gadget::commit_ivk(
@@ -604,7 +644,7 @@ impl plonk::Circuit<pallas::Base> for Circuit {
             let psi_new = assign_free_advice(
                 layouter.namespace(|| "witness psi_new"),
                 config.advices[0],
-                self.psi_new,
+                self.psi_new.as_ref().map(|psi_new| **psi_new),
             )?;
 
             let rcm_new = ScalarFixed::new(
@@ -689,9 +729,16 @@ impl plonk::Circuit<pallas::Base> for Circuit {
 
 /// The verifying key for the Orchard Action circuit.
 #[derive(Debug)]
-pub struct VerifyingKey {
-    params: halo2_proofs::poly::commitment::Params<vesta::Affine>,
-    vk: plonk::VerifyingKey<vesta::Affine>,
+pub struct VerifyingKey(VerifyingKeyInner);
+
+#[derive(Debug)]
+enum VerifyingKeyInner {
+    Halo2 {
+        params: halo2_proofs::poly::commitment::Params<vesta::Affine>,
+        vk: plonk::VerifyingKey<vesta::Affine>,
+    },
+    #[cfg(any(test, feature = "test-dependencies"))]
+    Fake,
 }
 
 impl VerifyingKey {
@@ -702,15 +749,32 @@ impl VerifyingKey {
 
         let vk = plonk::keygen_vk(&params, &circuit).unwrap();
 
-        VerifyingKey { params, vk }
+        VerifyingKey(VerifyingKeyInner::Halo2 { params, vk })
+    }
+
+    /// Returns a verifying key that accepts only the marker proofs created with
+    /// [`ProvingKey::fake`], and rejects every real proof.
+    ///
+    /// This provides no soundness whatsoever, and exists only to speed up tests.
+    #[cfg(any(test, feature = "test-dependencies"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "test-dependencies")))]
+    pub fn fake() -> Self {
+        VerifyingKey(VerifyingKeyInner::Fake)
     }
 }
 
 /// The proving key for the Orchard Action circuit.
 #[derive(Debug)]
-pub struct ProvingKey {
-    params: halo2_proofs::poly::commitment::Params<vesta::Affine>,
-    pk: plonk::ProvingKey<vesta::Affine>,
+pub struct ProvingKey(ProvingKeyInner);
+
+#[derive(Debug)]
+enum ProvingKeyInner {
+    Halo2 {
+        params: halo2_proofs::poly::commitment::Params<vesta::Affine>,
+        pk: plonk::ProvingKey<vesta::Affine>,
+    },
+    #[cfg(any(test, feature = "test-dependencies"))]
+    Fake,
 }
 
 impl ProvingKey {
@@ -722,7 +786,19 @@ impl ProvingKey {
         let vk = plonk::keygen_vk(&params, &circuit).unwrap();
         let pk = plonk::keygen_pk(&params, vk, &circuit).unwrap();
 
-        ProvingKey { params, pk }
+        ProvingKey(ProvingKeyInner::Halo2 { params, pk })
+    }
+
+    /// Returns a proving key that creates marker proofs instead of real ones.
+    ///
+    /// [`Proof::create`] checks the witnesses with halo2's `MockProver` and fails in the
+    /// same cases as real proving, but the resulting proof only verifies with
+    /// [`VerifyingKey::fake`]. This avoids the cost of building the proving key and of
+    /// proving, for downstream tests that exercise bundle construction.
+    #[cfg(any(test, feature = "test-dependencies"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "test-dependencies")))]
+    pub fn fake() -> Self {
+        ProvingKey(ProvingKeyInner::Fake)
     }
 }
 
@@ -833,6 +909,12 @@ impl Proof {
         instances: &[Instance],
         mut rng: impl RngCore,
     ) -> Result<Self, plonk::Error> {
+        let (params, pk) = match &pk.0 {
+            ProvingKeyInner::Halo2 { params, pk } => (params, pk),
+            #[cfg(any(test, feature = "test-dependencies"))]
+            ProvingKeyInner::Fake => return fake::create_proof(circuits, instances),
+        };
+
         let instances: Vec<_> = instances.iter().map(|i| i.to_halo2_instance()).collect();
         let instances: Vec<Vec<_>> = instances
             .iter()
@@ -841,19 +923,18 @@ impl Proof {
         let instances: Vec<_> = instances.iter().map(|i| &i[..]).collect();
 
         let mut transcript = Blake2bWrite::<_, vesta::Affine, _>::init(vec![]);
-        plonk::create_proof(
-            &pk.params,
-            &pk.pk,
-            circuits,
-            &instances,
-            &mut rng,
-            &mut transcript,
-        )?;
+        plonk::create_proof(params, pk, circuits, &instances, &mut rng, &mut transcript)?;
         Ok(Proof(transcript.finalize()))
     }
 
     /// Verifies this proof with the given instances.
     pub fn verify(&self, vk: &VerifyingKey, instances: &[Instance]) -> Result<(), plonk::Error> {
+        let (params, vk) = match &vk.0 {
+            VerifyingKeyInner::Halo2 { params, vk } => (params, vk),
+            #[cfg(any(test, feature = "test-dependencies"))]
+            VerifyingKeyInner::Fake => return fake::verify_proof(self, instances),
+        };
+
         let instances: Vec<_> = instances.iter().map(|i| i.to_halo2_instance()).collect();
         let instances: Vec<Vec<_>> = instances
             .iter()
@@ -861,9 +942,9 @@ impl Proof {
             .collect();
         let instances: Vec<_> = instances.iter().map(|i| &i[..]).collect();
 
-        let strategy = SingleVerifier::new(&vk.params);
+        let strategy = SingleVerifier::new(params);
         let mut transcript = Blake2bRead::init(&self.0[..]);
-        plonk::verify_proof(&vk.params, &vk.vk, strategy, &instances, &mut transcript)
+        plonk::verify_proof(params, vk, strategy, &instances, &mut transcript)
     }
 
     /// Constructs a new Proof value.
@@ -885,15 +966,16 @@ mod tests {
     use crate::{
         keys::SpendValidatingKey,
         note::Note,
+        spec::SecretField,
         tree::MerklePath,
         value::{ValueCommitTrapdoor, ValueCommitment},
     };
 
-    fn generate_circuit_instance<R: RngCore>(mut rng: R) -> (Circuit, Instance) {
+    pub(super) fn generate_circuit_instance<R: RngCore>(mut rng: R) -> (Circuit, Instance) {
         let (_, fvk, spent_note) = Note::dummy(&mut rng, None);
 
         let sender_address = spent_note.recipient();
-        let nk = *fvk.nk();
+        let nk = fvk.nk().clone();
         let rivk = fvk.rivk(fvk.scope_for_address(&spent_note.recipient()).unwrap());
         let nf_old = spent_note.nullifier(&fvk);
         let ak: SpendValidatingKey = fvk.into();
@@ -918,17 +1000,19 @@ mod tests {
                 pk_d_old: Some(*sender_address.pk_d()),
                 v_old: Some(spent_note.value()),
                 rho_old: Some(spent_note.rho()),
-                psi_old: Some(spent_note.rseed().psi(&spent_note.rho())),
+                psi_old: Some(SecretField::new(spent_note.rseed().psi(&spent_note.rho()))),
                 rcm_old: Some(spent_note.rseed().rcm(&spent_note.rho())),
                 cm_old: Some(spent_note.commitment()),
-                alpha: Some(alpha),
+                alpha: Some(SecretField::new(alpha)),
                 ak: Some(ak),
                 nk: Some(nk),
                 rivk: Some(rivk),
                 g_d_new: Some(output_note.recipient().g_d()),
                 pk_d_new: Some(*output_note.recipient().pk_d()),
                 v_new: Some(output_note.value()),
-                psi_new: Some(output_note.rseed().psi(&output_note.rho())),
+                psi_new: Some(SecretField::new(
+                    output_note.rseed().psi(&output_note.rho()),
+                )),
                 rcm_new: Some(output_note.rseed().rcm(&output_note.rho())),
                 rcv: Some(rcv),
             },
@@ -958,9 +1042,13 @@ mod tests {
         // Test that the pinned verification key (representing the circuit)
         // is as expected.
         {
-            // panic!("{:#?}", vk.vk.pinned());
+            let vk = match &vk.0 {
+                super::VerifyingKeyInner::Halo2 { vk, .. } => vk,
+                super::VerifyingKeyInner::Fake => unreachable!(),
+            };
+            // panic!("{:#?}", vk.pinned());
             assert_eq!(
-                format!("{:#?}\n", vk.vk.pinned()),
+                format!("{:#?}\n", vk.pinned()),
                 include_str!("circuit_description").replace("\r\n", "\n")
             );
         }
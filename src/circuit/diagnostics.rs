@@ -0,0 +1,277 @@
+//! Diagnostics for witnesses that do not satisfy the Orchard Action circuit.
+
+use std::fmt;
+
+use halo2_proofs::{
+    dev::{metadata, FailureLocation, MockProver, VerifyFailure},
+    plonk::{self, Any},
+};
+use pasta_curves::pallas;
+
+use super::{
+    report::{LayoutRecorder, RegionCategory},
+    Circuit, Instance, ANCHOR, CMX, CV_NET_X, CV_NET_Y, ENABLE_OUTPUT, ENABLE_SPEND, K, NF_OLD,
+    ORCHARD_CHECKS_CONSTRAINTS, ORCHARD_CHECKS_GATE, ORCHARD_CHECKS_GATE_INDEX, RK_X, RK_Y,
+};
+
+/// A statement enforced by the Action circuit.
+///
+/// These correspond to the Action statement defined in
+/// [Zcash Protocol Spec § 4.17.4: Action Statement (Orchard)][actionstatement].
+///
+/// [actionstatement]: https://zips.z.cash/protocol/nu5.pdf#actionstatement
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Check {
+    /// The commitment to the spent note, or to the output note, does not match the note,
+    /// or one of the note's fields is not canonically encoded.
+    NoteCommitment,
+    /// The spent note's address was not derived from the spending full viewing key.
+    DiversifiedAddress,
+    /// The Merkle path could not be hashed to a root.
+    MerklePath,
+    /// The Merkle path of a spent note with non-zero value does not lead to the anchor.
+    MerkleRoot,
+    /// The net value of the action does not equal the spent value minus the output
+    /// value.
+    ValueBalance,
+    /// The value commitment does not commit to the net value of the action.
+    ValueCommitment,
+    /// The nullifier was not derived from the spent note.
+    Nullifier,
+    /// The randomized validating key was not derived from the spending key.
+    SpendAuthority,
+    /// A note with non-zero value is spent while spends are disabled.
+    EnableSpends,
+    /// A note with non-zero value is created while outputs are disabled.
+    EnableOutputs,
+    /// The circuit could not be synthesized, for example because a witness is missing.
+    Synthesis,
+    /// Any other check.
+    Other,
+}
+
+impl Check {
+    fn from_region_category(category: RegionCategory) -> Self {
+        match category {
+            RegionCategory::NoteCommitment => Check::NoteCommitment,
+            RegionCategory::CommitIvk => Check::DiversifiedAddress,
+            RegionCategory::MerklePath => Check::MerklePath,
+            RegionCategory::ValueCommitment => Check::ValueCommitment,
+            RegionCategory::Nullifier => Check::Nullifier,
+            RegionCategory::Other => Check::Other,
+        }
+    }
+
+    fn from_public_input(offset: usize) -> Self {
+        match offset {
+            ANCHOR => Check::MerkleRoot,
+            CV_NET_X | CV_NET_Y => Check::ValueCommitment,
+            NF_OLD => Check::Nullifier,
+            RK_X | RK_Y => Check::SpendAuthority,
+            CMX => Check::NoteCommitment,
+            ENABLE_SPEND => Check::EnableSpends,
+            ENABLE_OUTPUT => Check::EnableOutputs,
+            _ => Check::Other,
+        }
+    }
+
+    /// Identifies the constraints of the gate enforcing the Orchard-specific checks.
+    fn from_constraint(constraint: &metadata::Constraint) -> Option<Self> {
+        let checks = [
+            Check::ValueBalance,
+            Check::MerkleRoot,
+            Check::EnableSpends,
+            Check::EnableOutputs,
+        ];
+        ORCHARD_CHECKS_CONSTRAINTS
+            .iter()
+            .zip(checks.iter())
+            .enumerate()
+            .find(|(index, (name, _))| {
+                let gate = metadata::Gate::from((ORCHARD_CHECKS_GATE_INDEX, ORCHARD_CHECKS_GATE));
+                *constraint == metadata::Constraint::from((gate, *index, **name))
+            })
+            .map(|(_, (_, check))| *check)
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Check::NoteCommitment => "note commitment integrity",
+            Check::DiversifiedAddress => "diversified address integrity",
+            Check::MerklePath => "Merkle path validity",
+            Check::MerkleRoot => "Merkle root does not match the anchor",
+            Check::ValueBalance => "net value does not match the note values",
+            Check::ValueCommitment => "value commitment integrity",
+            Check::Nullifier => "nullifier integrity",
+            Check::SpendAuthority => "spend authority",
+            Check::EnableSpends => "spend of a non-zero note while spends are disabled",
+            Check::EnableOutputs => "output of a non-zero note while outputs are disabled",
+            Check::Synthesis => "circuit synthesis",
+            Check::Other => "other circuit check",
+        })
+    }
+}
+
+/// A reason why a witness does not satisfy the Action circuit.
+#[derive(Debug)]
+pub struct WitnessFailure {
+    check: Check,
+    detail: String,
+}
+
+impl WitnessFailure {
+    /// Returns the check that failed.
+    pub fn check(&self) -> Check {
+        self.check
+    }
+
+    /// Returns a description of the failure in terms of the underlying constraint
+    /// system, naming the gate, region and cells involved.
+    pub fn detail(&self) -> &str {
+        &self.detail
+    }
+}
+
+impl fmt::Display for WitnessFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.check, self.detail.trim_end())
+    }
+}
+
+impl Circuit {
+    /// Checks this circuit's witness against the given public inputs, without creating
+    /// a proof.
+    ///
+    /// This runs the circuit through halo2's `MockProver`, which is much slower than
+    /// proving and should only be used to investigate why [`Proof::create`] fails, or
+    /// why a proof does not verify.
+    ///
+    /// [`Proof::create`]: super::Proof::create
+    pub fn diagnose(&self, instance: &Instance) -> Result<(), Vec<WitnessFailure>> {
+        let prover = MockProver::run(
+            K,
+            self,
+            instance
+                .to_halo2_instance()
+                .iter()
+                .map(|p| p.to_vec())
+                .collect(),
+        )
+        .map_err(|e| {
+            vec![WitnessFailure {
+                check: Check::Synthesis,
+                detail: e.to_string(),
+            }]
+        })?;
+
+        prover.verify().map_err(|failures| {
+            // MockProver numbers regions in the order they are laid out, so we can
+            // recover what each region is for by laying the circuit out ourselves.
+            let mut cs = plonk::ConstraintSystem::default();
+            let config = <Circuit as plonk::Circuit<pallas::Base>>::configure(&mut cs);
+            let regions = LayoutRecorder::record(self, config).regions;
+
+            failures
+                .into_iter()
+                .map(|failure| WitnessFailure {
+                    check: classify(&failure, &regions),
+                    detail: failure.to_string(),
+                })
+                .collect()
+        })
+    }
+}
+
+/// Determines which check of the Action circuit a `MockProver` failure belongs to.
+fn classify(failure: &VerifyFailure, regions: &[(String, RegionCategory)]) -> Check {
+    let in_region = |region: &metadata::Region| {
+        regions
+            .iter()
+            .enumerate()
+            .find(|(i, (name, _))| metadata::Region::from((*i, name.as_str())) == *region)
+            .map_or(Check::Other, |(_, (_, category))| {
+                Check::from_region_category(*category)
+            })
+    };
+    let at = |location: &FailureLocation| match location {
+        FailureLocation::InRegion { region, .. } => in_region(region),
+        FailureLocation::OutsideRegion { .. } => Check::Other,
+    };
+
+    match failure {
+        VerifyFailure::CellNotAssigned { region, .. } => in_region(region),
+        VerifyFailure::ConstraintNotSatisfied {
+            constraint,
+            location,
+            ..
+        } => Check::from_constraint(constraint).unwrap_or_else(|| at(location)),
+        VerifyFailure::ConstraintPoisoned { constraint } => {
+            Check::from_constraint(constraint).unwrap_or(Check::Other)
+        }
+        VerifyFailure::Lookup { location, .. } => at(location),
+        VerifyFailure::Permutation { column, location } => match location {
+            // Public inputs are the only cells outside of regions that are constrained
+            // by the permutation, and their row is their offset in the instance column.
+            FailureLocation::OutsideRegion { row }
+                if *column == metadata::Column::from((Any::Instance, 0)) =>
+            {
+                Check::from_public_input(*row)
+            }
+            _ => at(location),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::OsRng;
+
+    use super::Check;
+    use crate::{
+        circuit::tests::generate_circuit_instance,
+        note::Nullifier,
+        value::{NoteValue, ValueCommitTrapdoor, ValueCommitment, ValueSum},
+    };
+
+    #[test]
+    fn diagnose_bad_witnesses() {
+        let mut rng = OsRng;
+        let (circuit, instance) = generate_circuit_instance(&mut rng);
+        assert!(circuit.diagnose(&instance).is_ok());
+
+        // A nullifier that was not derived from the spent note.
+        {
+            let mut instance = instance.clone();
+            instance.nf_old = Nullifier::dummy(&mut rng);
+            let failures = circuit.diagnose(&instance).unwrap_err();
+            assert!(failures.iter().any(|f| f.check() == Check::Nullifier));
+        }
+
+        // A value commitment with a different trapdoor.
+        {
+            let mut instance = instance.clone();
+            instance.cv_net =
+                ValueCommitment::derive(ValueSum::zero(), ValueCommitTrapdoor::random(&mut rng));
+            let failures = circuit.diagnose(&instance).unwrap_err();
+            assert!(failures.iter().any(|f| f.check() == Check::ValueCommitment));
+        }
+
+        // A spent value that does not match the net value of the action.
+        {
+            let mut circuit = circuit.clone();
+            circuit.v_old = Some(NoteValue::from_raw(1));
+            let failures = circuit.diagnose(&instance).unwrap_err();
+            assert!(failures.iter().any(|f| f.check() == Check::ValueBalance));
+        }
+
+        // A missing witness.
+        {
+            let mut circuit = circuit.clone();
+            circuit.rcv = None;
+            let failures = circuit.diagnose(&instance).unwrap_err();
+            assert_eq!(failures[0].check(), Check::Synthesis);
+        }
+    }
+}
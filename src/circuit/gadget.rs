@@ -1,10 +1,26 @@
 //! Gadgets used in the Orchard circuit.
+//!
+//! These are the building blocks of the Action circuit, exposed so that other halo2
+//! circuits over the Pallas base field can enforce the same constraints: for example, a
+//! circuit that proves facts about Orchard notes, or that verifies Orchard actions
+//! recursively.
+//!
+//! Each gadget function takes the chips it needs as arguments. The ECC, Poseidon and
+//! Sinsemilla chips come from [`halo2_gadgets`], instantiated with the Orchard fixed
+//! bases and domains re-exported here. [`AddChip`], [`CommitIvkChip`] and
+//! [`NoteCommitChip`] are Orchard-specific, and are configured with their `configure`
+//! functions alongside the other chips in [`plonk::Circuit::configure`].
+//!
+//! [`AddChip`]: add_chip::AddChip
 
 use ff::Field;
 use pasta_curves::pallas;
 
-use super::{commit_ivk::CommitIvkChip, note_commit::NoteCommitChip};
-use crate::constants::{
+pub use super::{
+    commit_ivk::{CommitIvkChip, CommitIvkConfig},
+    note_commit::{NoteCommitChip, NoteCommitConfig},
+};
+pub use crate::constants::{
     NullifierK, OrchardCommitDomains, OrchardFixedBases, OrchardFixedBasesFull, OrchardHashDomains,
     ValueCommitV,
 };
@@ -25,7 +41,7 @@ use halo2_proofs::{
     plonk::{self, Advice, Assigned, Column},
 };
 
-pub(in crate::circuit) mod add_chip;
+pub mod add_chip;
 
 impl super::Config {
     pub(super) fn add_chip(&self) -> add_chip::AddChip {
@@ -78,7 +94,7 @@ impl super::Config {
 }
 
 /// An instruction set for adding two circuit words (field elements).
-pub(in crate::circuit) trait AddInstruction<F: FieldExt>: Chip<F> {
+pub trait AddInstruction<F: FieldExt>: Chip<F> {
     /// Constraints `a + b` and returns the sum.
     fn add(
         &self,
@@ -93,7 +109,7 @@ pub(in crate::circuit) trait AddInstruction<F: FieldExt>: Chip<F> {
 /// Usages of this helper are technically superfluous, as the single-cell region is only
 /// ever used in equality constraints. We could eliminate them with a
 /// [write-on-copy abstraction](https://github.com/zcash/halo2/issues/334).
-pub(in crate::circuit) fn assign_free_advice<F: Field, V: Copy>(
+pub fn assign_free_advice<F: Field, V: Copy>(
     mut layouter: impl Layouter<F>,
     column: Column<Advice>,
     value: Option<V>,
@@ -117,7 +133,7 @@ where
 /// `ValueCommit^Orchard` from [Section 5.4.8.3 Homomorphic Pedersen commitments (Sapling and Orchard)].
 ///
 /// [Section 5.4.8.3 Homomorphic Pedersen commitments (Sapling and Orchard)]: https://zips.z.cash/protocol/protocol.pdf#concretehomomorphiccommit
-pub(in crate::circuit) fn value_commit_orchard<
+pub fn value_commit_orchard<
     EccChip: EccInstructions<
         pallas::Affine,
         FixedPoints = OrchardFixedBases,
@@ -153,7 +169,7 @@ pub(in crate::circuit) fn value_commit_orchard<
 ///
 /// [Section 4.16: Note Commitments and Nullifiers]: https://zips.z.cash/protocol/protocol.pdf#commitmentsandnullifiers
 #[allow(clippy::too_many_arguments)]
-pub(in crate::circuit) fn derive_nullifier<
+pub fn derive_nullifier<
     PoseidonChip: PoseidonSpongeInstructions<pallas::Base, poseidon::P128Pow5T3, ConstantLength<2>, 3, 2>,
     AddChip: AddInstruction<pallas::Base>,
     EccChip: EccInstructions<
@@ -207,5 +223,147 @@ pub(in crate::circuit) fn derive_nullifier<
         .map(|res| res.extract_p())
 }
 
-pub(in crate::circuit) use crate::circuit::commit_ivk::gadgets::commit_ivk;
-pub(in crate::circuit) use crate::circuit::note_commit::gadgets::note_commit;
+pub use crate::circuit::commit_ivk::gadgets::commit_ivk;
+pub use crate::circuit::note_commit::gadgets::note_commit;
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use halo2_gadgets::{
+        ecc::{
+            chip::{EccChip, EccConfig},
+            ScalarFixed, ScalarFixedShort,
+        },
+        sinsemilla::chip::{SinsemillaChip, SinsemillaConfig},
+        utilities::lookup_range_check::LookupRangeCheckConfig,
+    };
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        plonk::{self, Advice, Circuit, Column, ConstraintSystem, Instance},
+    };
+    use pasta_curves::pallas;
+    use rand::rngs::OsRng;
+
+    use super::{
+        assign_free_advice, value_commit_orchard, OrchardCommitDomains, OrchardFixedBases,
+        OrchardHashDomains,
+    };
+    use crate::value::{NoteValue, ValueCommitTrapdoor, ValueCommitment};
+
+    /// A circuit outside of the Action circuit that uses the public gadgets to commit to
+    /// a positive value.
+    #[derive(Default)]
+    struct ValueCommitCircuit {
+        value: Option<pallas::Base>,
+        rcv: Option<pallas::Scalar>,
+    }
+
+    impl Circuit<pallas::Base> for ValueCommitCircuit {
+        type Config = (
+            SinsemillaConfig<OrchardHashDomains, OrchardCommitDomains, OrchardFixedBases>,
+            EccConfig<OrchardFixedBases>,
+            [Column<Advice>; 10],
+            Column<Instance>,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [(); 10].map(|()| meta.advice_column());
+            for advice in advices.iter() {
+                meta.enable_equality(*advice);
+            }
+            let primary = meta.instance_column();
+            meta.enable_equality(primary);
+
+            let lagrange_coeffs = [(); 8].map(|()| meta.fixed_column());
+            meta.enable_constant(lagrange_coeffs[0]);
+
+            let table_idx = meta.lookup_table_column();
+            let lookup = (
+                table_idx,
+                meta.lookup_table_column(),
+                meta.lookup_table_column(),
+            );
+            let range_check = LookupRangeCheckConfig::configure(meta, advices[9], table_idx);
+
+            // The Sinsemilla chip is only used to load the lookup table.
+            let sinsemilla_config = SinsemillaChip::configure(
+                meta,
+                advices[..5].try_into().unwrap(),
+                advices[6],
+                lagrange_coeffs[0],
+                lookup,
+                range_check,
+            );
+            let ecc_config = EccChip::<OrchardFixedBases>::configure(
+                meta,
+                advices,
+                lagrange_coeffs,
+                range_check,
+            );
+
+            (sinsemilla_config, ecc_config, advices, primary)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), plonk::Error> {
+            let (sinsemilla_config, ecc_config, advices, primary) = config;
+            SinsemillaChip::load(sinsemilla_config, &mut layouter)?;
+            let ecc_chip = EccChip::construct(ecc_config);
+
+            let magnitude =
+                assign_free_advice(layouter.namespace(|| "magnitude"), advices[0], self.value)?;
+            // The sign is fixed to a constant, so that the prover cannot negate the value.
+            let sign = layouter.assign_region(
+                || "sign",
+                |mut region| {
+                    region.assign_advice_from_constant(
+                        || "sign",
+                        advices[0],
+                        0,
+                        pallas::Base::one(),
+                    )
+                },
+            )?;
+            let v = ScalarFixedShort::new(
+                ecc_chip.clone(),
+                layouter.namespace(|| "v"),
+                (magnitude, sign),
+            )?;
+            let rcv = ScalarFixed::new(ecc_chip.clone(), layouter.namespace(|| "rcv"), self.rcv)?;
+
+            let cv = value_commit_orchard(layouter.namespace(|| "cv"), ecc_chip, v, rcv)?;
+            layouter.constrain_instance(cv.inner().x().cell(), primary, 0)?;
+            layouter.constrain_instance(cv.inner().y().cell(), primary, 1)
+        }
+    }
+
+    #[test]
+    fn external_value_commitment() {
+        let mut rng = OsRng;
+
+        let value = NoteValue::from_raw(1_234_567);
+        let rcv = ValueCommitTrapdoor::random(&mut rng);
+        let cv = ValueCommitment::derive(value - NoteValue::zero(), rcv.clone());
+
+        let circuit = ValueCommitCircuit {
+            value: Some(pallas::Base::from(value.inner())),
+            rcv: Some(rcv.inner()),
+        };
+        let prover = MockProver::run(11, &circuit, vec![vec![cv.x(), cv.y()]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let other =
+            ValueCommitment::derive(NoteValue::from_raw(7_654_321) - NoteValue::zero(), rcv);
+        let prover = MockProver::run(11, &circuit, vec![vec![other.x(), other.y()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -1,3 +1,5 @@
+//! A chip for adding two field elements.
+
 use halo2_proofs::{
     circuit::{AssignedCell, Chip, Layouter},
     plonk::{self, Advice, Column, ConstraintSystem, Constraints, Selector},
@@ -7,8 +9,9 @@ use pasta_curves::pallas;
 
 use super::AddInstruction;
 
+/// Configuration for the [`AddChip`].
 #[derive(Clone, Debug)]
-pub(in crate::circuit) struct AddConfig {
+pub struct AddConfig {
     a: Column<Advice>,
     b: Column<Advice>,
     c: Column<Advice>,
@@ -16,7 +19,8 @@ pub(in crate::circuit) struct AddConfig {
 }
 
 /// A chip implementing a single addition constraint `c = a + b` on a single row.
-pub(in crate::circuit) struct AddChip {
+#[derive(Clone, Debug)]
+pub struct AddChip {
     config: AddConfig,
 }
 
@@ -34,7 +38,9 @@ impl Chip<pallas::Base> for AddChip {
 }
 
 impl AddChip {
-    pub(in crate::circuit) fn configure(
+    /// Configures the chip to constrain `c = a + b` over the given advice columns, and
+    /// returns its configuration.
+    pub fn configure(
         meta: &mut ConstraintSystem<pallas::Base>,
         a: Column<Advice>,
         b: Column<Advice>,
@@ -53,7 +59,8 @@ impl AddChip {
         AddConfig { a, b, c, q_add }
     }
 
-    pub(in crate::circuit) fn construct(config: AddConfig) -> Self {
+    /// Constructs a chip from its configuration.
+    pub fn construct(config: AddConfig) -> Self {
         Self { config }
     }
 }
@@ -1412,6 +1412,7 @@ impl YCanonicity {
     }
 }
 
+/// Configuration for the [`NoteCommitChip`].
 #[allow(non_snake_case)]
 #[derive(Clone, Debug)]
 pub struct NoteCommitConfig {
@@ -1431,15 +1432,19 @@ pub struct NoteCommitConfig {
         SinsemillaConfig<OrchardHashDomains, OrchardCommitDomains, OrchardFixedBases>,
 }
 
+/// A chip that constrains the decomposition and canonicity of the inputs to
+/// `NoteCommit^Orchard`.
 #[derive(Clone, Debug)]
 pub struct NoteCommitChip {
     config: NoteCommitConfig,
 }
 
 impl NoteCommitChip {
+    /// Configures the chip to use the given advice columns and Sinsemilla instance, and
+    /// returns its configuration.
     #[allow(non_snake_case)]
     #[allow(clippy::many_single_char_names)]
-    pub(in crate::circuit) fn configure(
+    pub fn configure(
         meta: &mut ConstraintSystem<pallas::Base>,
         advices: [Column<Advice>; 10],
         sinsemilla_config: SinsemillaConfig<
@@ -1558,7 +1563,8 @@ impl NoteCommitChip {
         }
     }
 
-    pub(in crate::circuit) fn construct(config: NoteCommitConfig) -> Self {
+    /// Constructs a chip from its configuration.
+    pub fn construct(config: NoteCommitConfig) -> Self {
         Self { config }
     }
 }
@@ -1568,10 +1574,13 @@ pub(in crate::circuit) mod gadgets {
 
     use super::*;
 
+    /// `NoteCommit^Orchard` from [Section 5.4.8.4 Sinsemilla commitments].
+    ///
+    /// [Section 5.4.8.4 Sinsemilla commitments]: https://zips.z.cash/protocol/protocol.pdf#concretesinsemillacommit
     #[allow(clippy::many_single_char_names)]
     #[allow(clippy::type_complexity)]
     #[allow(clippy::too_many_arguments)]
-    pub(in crate::circuit) fn note_commit(
+    pub fn note_commit(
         mut layouter: impl Layouter<pallas::Base>,
         chip: SinsemillaChip<OrchardHashDomains, OrchardCommitDomains, OrchardFixedBases>,
         ecc_chip: EccChip<OrchardFixedBases>,
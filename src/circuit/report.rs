@@ -0,0 +1,434 @@
+//! Cost and layout reporting for the Orchard Action circuit.
+
+use std::{collections::BTreeMap, fmt};
+
+use halo2_proofs::{
+    circuit::floor_planner,
+    dev::CircuitCost,
+    plonk::{
+        self, Advice, Any, Assigned, Assignment, Column, ConstraintSystem, Error, Fixed,
+        FloorPlanner, Instance, Selector,
+    },
+};
+use pasta_curves::{pallas, vesta};
+
+use super::{Circuit, Config, K};
+
+/// A group of regions in the Orchard Action circuit, identified by the top-level
+/// namespace in which they are laid out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RegionCategory {
+    /// Note commitment integrity, for both the spent and the output note.
+    NoteCommitment,
+    /// Derivation of the incoming viewing key with $\mathsf{Commit}^\mathsf{ivk}$.
+    CommitIvk,
+    /// Merkle path validity.
+    MerklePath,
+    /// Value commitment integrity.
+    ValueCommitment,
+    /// Nullifier integrity.
+    Nullifier,
+    /// Everything else: witnessing, spend authority, the diversified address check,
+    /// lookup tables, and the remaining Orchard circuit checks.
+    Other,
+}
+
+impl RegionCategory {
+    /// Every category, in reporting order.
+    pub const ALL: [RegionCategory; 6] = [
+        RegionCategory::NoteCommitment,
+        RegionCategory::CommitIvk,
+        RegionCategory::MerklePath,
+        RegionCategory::ValueCommitment,
+        RegionCategory::Nullifier,
+        RegionCategory::Other,
+    ];
+
+    /// Maps a namespace used in [`Circuit::synthesize`] to its category.
+    ///
+    /// [`Circuit::synthesize`]: plonk::Circuit::synthesize
+    fn from_namespace(namespace: &str) -> Option<Self> {
+        match namespace {
+            "g★_d || pk★_d || i2lebsp_{64}(v) || i2lebsp_{255}(rho) || i2lebsp_{255}(psi)" => {
+                Some(RegionCategory::NoteCommitment)
+            }
+            "CommitIvk" => Some(RegionCategory::CommitIvk),
+            "Merkle path" => Some(RegionCategory::MerklePath),
+            "cv_net = ValueCommit^Orchard_rcv(v_net)" => Some(RegionCategory::ValueCommitment),
+            "nf_old = DeriveNullifier_nk(rho_old, psi_old, cm_old)" => {
+                Some(RegionCategory::Nullifier)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RegionCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RegionCategory::NoteCommitment => "note commitment",
+            RegionCategory::CommitIvk => "CommitIvk",
+            RegionCategory::MerklePath => "Merkle path",
+            RegionCategory::ValueCommitment => "value commitment",
+            RegionCategory::Nullifier => "nullifier",
+            RegionCategory::Other => "other",
+        })
+    }
+}
+
+/// A summary of the size and layout of the Orchard Action circuit.
+///
+/// This is intended for quantifying the impact of changes to the circuit, and for
+/// estimating the cost of proving and verifying bundles. It can be built without any
+/// proving or verifying keys.
+///
+/// halo2 does not expose the custom gates of a constraint system, so they are not
+/// counted here; `halo2_proofs::dev::CircuitGates` can print them instead.
+///
+/// # Examples
+///
+/// ```
+/// use orchard::circuit::{CircuitReport, RegionCategory};
+///
+/// let report = CircuitReport::build();
+/// assert!(report.rows_used() <= report.usable_rows());
+/// assert!(report.region_rows(RegionCategory::MerklePath) > 0);
+/// println!("{}", report);
+/// ```
+#[derive(Debug)]
+pub struct CircuitReport {
+    usable_rows: usize,
+    rows_used: usize,
+    advice_columns: usize,
+    fixed_columns: usize,
+    instance_columns: usize,
+    selectors: usize,
+    lookups: usize,
+    max_degree: usize,
+    regions: usize,
+    region_rows: BTreeMap<RegionCategory, usize>,
+    cost: CircuitCost<vesta::Point, Circuit>,
+}
+
+impl CircuitReport {
+    /// Lays out the Orchard Action circuit and measures it.
+    pub fn build() -> Self {
+        let circuit = Circuit::default();
+
+        let mut cs = ConstraintSystem::default();
+        let config = <Circuit as plonk::Circuit<pallas::Base>>::configure(&mut cs);
+
+        let recorder = LayoutRecorder::record(&circuit, config);
+
+        // Lookup arguments are numbered sequentially, so the index of a new one is the
+        // number that already exist.
+        let lookups = cs.clone().lookup(|_| vec![]);
+
+        CircuitReport {
+            usable_rows: (1 << K) - (cs.blinding_factors() + 1),
+            rows_used: recorder.rows_used,
+            advice_columns: recorder.advice_columns.len(),
+            fixed_columns: recorder.fixed_columns.len(),
+            instance_columns: recorder.instance_columns.len(),
+            selectors: recorder.selectors.len(),
+            lookups,
+            max_degree: cs.degree(),
+            regions: recorder.regions.len(),
+            region_rows: recorder.region_rows,
+            cost: CircuitCost::measure(K as usize, &circuit),
+        }
+    }
+
+    /// Returns $k$, where the circuit has $2^k$ rows.
+    pub fn k(&self) -> u32 {
+        K
+    }
+
+    /// Returns the number of rows that can be assigned, after excluding the rows that
+    /// are reserved for blinding factors.
+    pub fn usable_rows(&self) -> usize {
+        self.usable_rows
+    }
+
+    /// Returns the number of rows occupied by the circuit layout, including lookup
+    /// tables.
+    pub fn rows_used(&self) -> usize {
+        self.rows_used
+    }
+
+    /// Returns the number of advice columns.
+    ///
+    /// Columns are counted as they are used by the circuit layout, as are
+    /// [`Self::fixed_columns`], [`Self::instance_columns`] and [`Self::selectors`].
+    pub fn advice_columns(&self) -> usize {
+        self.advice_columns
+    }
+
+    /// Returns the number of fixed columns.
+    ///
+    /// This does not include the fixed columns into which the selectors are combined
+    /// during key generation.
+    pub fn fixed_columns(&self) -> usize {
+        self.fixed_columns
+    }
+
+    /// Returns the number of instance columns.
+    pub fn instance_columns(&self) -> usize {
+        self.instance_columns
+    }
+
+    /// Returns the number of selectors.
+    pub fn selectors(&self) -> usize {
+        self.selectors
+    }
+
+    /// Returns the number of lookup arguments.
+    pub fn lookups(&self) -> usize {
+        self.lookups
+    }
+
+    /// Returns the maximum degree of the constraint system.
+    pub fn max_degree(&self) -> usize {
+        self.max_degree
+    }
+
+    /// Returns the number of regions in the circuit layout.
+    pub fn regions(&self) -> usize {
+        self.regions
+    }
+
+    /// Returns the number of rows spanned by the regions in the given category.
+    ///
+    /// The floor planner places regions side by side where their columns do not
+    /// overlap, so the sum over all categories can exceed [`Self::rows_used`].
+    pub fn region_rows(&self, category: RegionCategory) -> usize {
+        self.region_rows.get(&category).copied().unwrap_or(0)
+    }
+
+    /// Returns the size in bytes of a proof for a bundle with `num_actions` actions.
+    pub fn proof_size(&self, num_actions: usize) -> usize {
+        self.cost.proof_size(num_actions).into()
+    }
+
+    /// Returns the size in bytes by which a proof grows for each additional action.
+    pub fn marginal_proof_size(&self) -> usize {
+        self.cost.marginal_proof_size().into()
+    }
+}
+
+impl fmt::Display for CircuitReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Orchard Action circuit (k = {})", self.k())?;
+        writeln!(
+            f,
+            "Rows used: {} of {} usable",
+            self.rows_used, self.usable_rows
+        )?;
+        writeln!(
+            f,
+            "Columns: {} advice, {} fixed, {} instance; {} selectors",
+            self.advice_columns, self.fixed_columns, self.instance_columns, self.selectors
+        )?;
+        writeln!(
+            f,
+            "Max degree: {}; lookups: {}",
+            self.max_degree, self.lookups
+        )?;
+        writeln!(f, "Regions: {}", self.regions)?;
+        for category in RegionCategory::ALL.iter() {
+            writeln!(f, "- {}: {} rows", category, self.region_rows(*category))?;
+        }
+        writeln!(
+            f,
+            "Proof size: {} bytes + {} bytes per action",
+            self.proof_size(0),
+            self.marginal_proof_size()
+        )
+    }
+}
+
+/// An [`Assignment`] that discards all values, and records the category of each region
+/// of the circuit, the rows it occupies, and the columns and selectors it uses.
+#[derive(Debug, Default)]
+pub(super) struct LayoutRecorder {
+    namespaces: Vec<String>,
+    /// The category of the current region, and the first and last rows it occupies.
+    current: Option<(RegionCategory, Option<(usize, usize)>)>,
+    /// The name and category of each region, in the order they were laid out.
+    pub(super) regions: Vec<(String, RegionCategory)>,
+    region_rows: BTreeMap<RegionCategory, usize>,
+    rows_used: usize,
+    advice_columns: Vec<Column<Advice>>,
+    fixed_columns: Vec<Column<Fixed>>,
+    instance_columns: Vec<Column<Instance>>,
+    selectors: Vec<Selector>,
+}
+
+impl LayoutRecorder {
+    /// Lays out `circuit` with the floor planner used by the prover.
+    pub(super) fn record(circuit: &Circuit, config: Config) -> Self {
+        let constants = config.constants;
+        let mut recorder = LayoutRecorder::default();
+        floor_planner::V1::synthesize(&mut recorder, circuit, config, vec![constants])
+            .expect("the Orchard circuit can always be laid out");
+        recorder
+    }
+
+    fn insert_unique<T: PartialEq>(items: &mut Vec<T>, item: T) {
+        if !items.contains(&item) {
+            items.push(item);
+        }
+    }
+
+    fn occupy(&mut self, row: usize) {
+        self.rows_used = self.rows_used.max(row + 1);
+        if let Some((_, span)) = self.current.as_mut() {
+            *span = Some(match span {
+                Some((start, end)) => ((*start).min(row), (*end).max(row)),
+                None => (row, row),
+            });
+        }
+    }
+}
+
+impl Assignment<pallas::Base> for LayoutRecorder {
+    fn enter_region<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let category = self
+            .namespaces
+            .iter()
+            .find_map(|namespace| RegionCategory::from_namespace(namespace))
+            .unwrap_or(RegionCategory::Other);
+        self.regions.push((name_fn().into(), category));
+        self.current = Some((category, None));
+    }
+
+    fn exit_region(&mut self) {
+        if let Some((category, span)) = self.current.take() {
+            *self.region_rows.entry(category).or_default() +=
+                span.map_or(0, |(start, end)| end - start + 1);
+        }
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Self::insert_unique(&mut self.selectors, *selector);
+        self.occupy(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Option<pallas::Base>, Error> {
+        Ok(None)
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Advice>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<pallas::Base>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Self::insert_unique(&mut self.advice_columns, column);
+        self.occupy(row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Fixed>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<pallas::Base>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Self::insert_unique(&mut self.fixed_columns, column);
+        self.occupy(row);
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        left: Column<Any>,
+        _: usize,
+        right: Column<Any>,
+        _: usize,
+    ) -> Result<(), Error> {
+        // Public inputs are only ever copied into the circuit, so this is where we see
+        // the instance columns.
+        for column in [left, right] {
+            if let Ok(column) = Column::<Instance>::try_from(column) {
+                Self::insert_unique(&mut self.instance_columns, column);
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        _: usize,
+        _: Option<Assigned<pallas::Base>>,
+    ) -> Result<(), Error> {
+        Self::insert_unique(&mut self.fixed_columns, column);
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.namespaces.push(name_fn().into());
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {
+        self.namespaces.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CircuitReport, RegionCategory};
+
+    #[test]
+    fn action_circuit_report() {
+        let report = CircuitReport::build();
+
+        assert_eq!(report.k(), 11);
+        assert_eq!(report.advice_columns(), 10);
+        assert_eq!(report.instance_columns(), 1);
+        assert!(report.fixed_columns() > 0 && report.selectors() > 0);
+        // These match the proof sizes pinned in `circuit::tests::round_trip`.
+        assert_eq!(report.proof_size(1), 4992);
+        assert_eq!(report.proof_size(2), 7264);
+        assert_eq!(
+            report.proof_size(2) - report.proof_size(1),
+            report.marginal_proof_size()
+        );
+
+        assert!(report.rows_used() <= report.usable_rows());
+        assert!(report.max_degree() > 0 && report.lookups() > 0);
+
+        // Every named category must match a namespace in `Circuit::synthesize`.
+        for category in RegionCategory::ALL.iter() {
+            assert!(report.region_rows(*category) > 0, "{} is empty", category);
+        }
+    }
+}
@@ -0,0 +1,277 @@
+//! Requests to prove the Action circuits of a bundle.
+
+use std::io::{self, Read, Write};
+
+use ff::PrimeField;
+use group::GroupEncoding;
+use halo2_proofs::plonk;
+use pasta_curves::pallas;
+use rand::RngCore;
+use subtle::CtOption;
+
+use super::{Circuit, Instance, Proof, ProvingKey};
+use crate::{
+    constants::MERKLE_DEPTH_ORCHARD,
+    keys::{
+        CommitIvkRandomness, DiversifiedTransmissionKey, NullifierDerivingKey, SpendValidatingKey,
+    },
+    note::{
+        commitment::{NoteCommitTrapdoor, NoteCommitment},
+        ExtractedNoteCommitment, Nullifier,
+    },
+    primitives::redpallas,
+    spec::{NonIdentityPallasPoint, SecretField},
+    tree::{Anchor, MerkleHashOrchard},
+    value::{NoteValue, ValueCommitTrapdoor, ValueCommitment},
+};
+
+/// The witnesses and public inputs of the Action circuits of a bundle.
+///
+/// This is everything needed to create the [`Proof`] for a bundle. It does not contain
+/// spend authorizing keys, signing metadata or note ciphertexts, so proving can be
+/// outsourced by sending a `ProvingRequest` to another party.
+///
+/// # Security
+///
+/// Sending a `ProvingRequest` hands over the full viewing key of the scope of each
+/// spent note. Besides the spent and created notes themselves, each spend carries `ak`,
+/// `nk` and `rivk`, from which the prover can derive the incoming and outgoing viewing
+/// keys for that scope, decrypt every note the account receives or sends in it, and
+/// detect when those notes are spent. Only send requests to a prover that you would
+/// trust with those full viewing keys. The prover cannot authorize spends.
+///
+/// The prover may also return an invalid proof. The only guarantee of a proof's
+/// integrity is the verification in [`Bundle::apply_proof`], which every outsourced
+/// proof must pass before the bundle is signed.
+///
+/// Created by [`Bundle::proving_request`], and proven with
+/// [`Proof::create_from_request`].
+///
+/// [`Bundle::proving_request`]: crate::Bundle::proving_request
+/// [`Bundle::apply_proof`]: crate::Bundle::apply_proof
+#[derive(Clone, Debug)]
+pub struct ProvingRequest {
+    circuits: Vec<Circuit>,
+    instances: Vec<Instance>,
+}
+
+pub(super) fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_owned())
+}
+
+fn witness<T>(value: Option<T>) -> io::Result<T> {
+    value.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Missing witness"))
+}
+
+pub(super) fn read_bytes<R: Read, const N: usize>(mut reader: R) -> io::Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+pub(super) fn read_32<R: Read, T>(
+    reader: R,
+    parse: impl FnOnce(&[u8; 32]) -> CtOption<T>,
+    msg: &str,
+) -> io::Result<T> {
+    Option::from(parse(&read_bytes(reader)?)).ok_or_else(|| invalid(msg))
+}
+
+fn read_bool<R: Read>(reader: R) -> io::Result<bool> {
+    match read_bytes::<_, 1>(reader)? {
+        [0] => Ok(false),
+        [1] => Ok(true),
+        _ => Err(invalid("Invalid boolean")),
+    }
+}
+
+fn write_circuit<W: Write>(circuit: &Circuit, mut writer: W) -> io::Result<()> {
+    for node in witness(circuit.path)?.iter() {
+        writer.write_all(&node.to_bytes())?;
+    }
+    writer.write_all(&witness(circuit.pos)?.to_le_bytes())?;
+    writer.write_all(&witness(circuit.g_d_old)?.to_bytes())?;
+    writer.write_all(&witness(circuit.pk_d_old)?.to_bytes())?;
+    writer.write_all(&witness(circuit.v_old)?.to_bytes())?;
+    writer.write_all(&witness(circuit.rho_old)?.to_bytes())?;
+    writer.write_all(&witness(circuit.psi_old.as_ref())?.to_repr())?;
+    writer.write_all(&witness(circuit.rcm_old.as_ref())?.to_bytes())?;
+    writer.write_all(&witness(circuit.cm_old.as_ref())?.to_bytes())?;
+    writer.write_all(&witness(circuit.alpha.as_ref())?.to_repr())?;
+    writer.write_all(&witness(circuit.ak.as_ref())?.to_bytes())?;
+    writer.write_all(&witness(circuit.nk.as_ref())?.to_bytes())?;
+    writer.write_all(&witness(circuit.rivk.as_ref())?.to_bytes())?;
+    writer.write_all(&witness(circuit.g_d_new)?.to_bytes())?;
+    writer.write_all(&witness(circuit.pk_d_new)?.to_bytes())?;
+    writer.write_all(&witness(circuit.v_new)?.to_bytes())?;
+    writer.write_all(&witness(circuit.psi_new.as_ref())?.to_repr())?;
+    writer.write_all(&witness(circuit.rcm_new.as_ref())?.to_bytes())?;
+    writer.write_all(&witness(circuit.rcv.as_ref())?.to_bytes())
+}
+
+fn read_circuit<R: Read>(mut reader: R) -> io::Result<Circuit> {
+    let path: Vec<_> = (0..MERKLE_DEPTH_ORCHARD)
+        .map(|_| read_32(&mut reader, MerkleHashOrchard::from_bytes, "Invalid path"))
+        .collect::<io::Result<_>>()?;
+    let path: [MerkleHashOrchard; MERKLE_DEPTH_ORCHARD] = path.try_into().unwrap();
+    let pos = u32::from_le_bytes(read_bytes(&mut reader)?);
+    let g_d_old = read_32(
+        &mut reader,
+        NonIdentityPallasPoint::from_bytes,
+        "Invalid g_d",
+    )?;
+    let pk_d_old = read_32(
+        &mut reader,
+        DiversifiedTransmissionKey::from_bytes,
+        "Invalid pk_d",
+    )?;
+    let v_old = NoteValue::from_bytes(read_bytes(&mut reader)?);
+    let rho_old = read_32(&mut reader, Nullifier::from_bytes, "Invalid rho")?;
+    let psi_old = read_32(&mut reader, |b| pallas::Base::from_repr(*b), "Invalid psi")?;
+    let rcm_old = read_32(&mut reader, NoteCommitTrapdoor::from_bytes, "Invalid rcm")?;
+    let cm_old = read_32(&mut reader, NoteCommitment::from_bytes, "Invalid cm")?;
+    let alpha = read_32(
+        &mut reader,
+        |b| pallas::Scalar::from_repr(*b),
+        "Invalid alpha",
+    )?;
+    let ak = SpendValidatingKey::from_bytes(&read_bytes::<_, 32>(&mut reader)?)
+        .ok_or_else(|| invalid("Invalid ak"))?;
+    let nk = NullifierDerivingKey::from_bytes(&read_bytes::<_, 32>(&mut reader)?)
+        .ok_or_else(|| invalid("Invalid nk"))?;
+    let rivk = CommitIvkRandomness::from_bytes(&read_bytes::<_, 32>(&mut reader)?)
+        .ok_or_else(|| invalid("Invalid rivk"))?;
+    let g_d_new = read_32(
+        &mut reader,
+        NonIdentityPallasPoint::from_bytes,
+        "Invalid g_d",
+    )?;
+    let pk_d_new = read_32(
+        &mut reader,
+        DiversifiedTransmissionKey::from_bytes,
+        "Invalid pk_d",
+    )?;
+    let v_new = NoteValue::from_bytes(read_bytes(&mut reader)?);
+    let psi_new = read_32(&mut reader, |b| pallas::Base::from_repr(*b), "Invalid psi")?;
+    let rcm_new = read_32(&mut reader, NoteCommitTrapdoor::from_bytes, "Invalid rcm")?;
+    let rcv = read_32(&mut reader, ValueCommitTrapdoor::from_bytes, "Invalid rcv")?;
+
+    Ok(Circuit {
+        path: Some(path),
+        pos: Some(pos),
+        g_d_old: Some(g_d_old),
+        pk_d_old: Some(pk_d_old),
+        v_old: Some(v_old),
+        rho_old: Some(rho_old),
+        psi_old: Some(SecretField::new(psi_old)),
+        rcm_old: Some(rcm_old),
+        cm_old: Some(cm_old),
+        alpha: Some(SecretField::new(alpha)),
+        ak: Some(ak),
+        nk: Some(nk),
+        rivk: Some(rivk),
+        g_d_new: Some(g_d_new),
+        pk_d_new: Some(pk_d_new),
+        v_new: Some(v_new),
+        psi_new: Some(SecretField::new(psi_new)),
+        rcm_new: Some(rcm_new),
+        rcv: Some(rcv),
+    })
+}
+
+fn write_instance<W: Write>(instance: &Instance, mut writer: W) -> io::Result<()> {
+    writer.write_all(&instance.anchor.to_bytes())?;
+    writer.write_all(&instance.cv_net.to_bytes())?;
+    writer.write_all(&instance.nf_old.to_bytes())?;
+    writer.write_all(&<[u8; 32]>::from(&instance.rk))?;
+    writer.write_all(&instance.cmx.to_bytes())?;
+    writer.write_all(&[
+        u8::from(instance.enable_spend),
+        u8::from(instance.enable_output),
+    ])
+}
+
+fn read_instance<R: Read>(mut reader: R) -> io::Result<Instance> {
+    let anchor = read_32(&mut reader, |b| Anchor::from_bytes(*b), "Invalid anchor")?;
+    let cv_net = read_32(&mut reader, ValueCommitment::from_bytes, "Invalid cv_net")?;
+    let nf_old = read_32(&mut reader, Nullifier::from_bytes, "Invalid nullifier")?;
+    let rk = redpallas::VerificationKey::try_from(read_bytes::<_, 32>(&mut reader)?)
+        .map_err(|_| invalid("Invalid rk"))?;
+    let cmx = read_32(
+        &mut reader,
+        ExtractedNoteCommitment::from_bytes,
+        "Invalid cmx",
+    )?;
+    let enable_spend = read_bool(&mut reader)?;
+    let enable_output = read_bool(&mut reader)?;
+
+    Ok(Instance::from_parts(
+        anchor,
+        cv_net,
+        nf_old,
+        rk,
+        cmx,
+        enable_spend,
+        enable_output,
+    ))
+}
+
+impl ProvingRequest {
+    pub(crate) fn from_parts(circuits: Vec<Circuit>, instances: Vec<Instance>) -> Self {
+        ProvingRequest {
+            circuits,
+            instances,
+        }
+    }
+
+    /// Returns the public inputs of the Action circuits, in the order of the actions of
+    /// the bundle.
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
+    }
+
+    /// Writes this request.
+    ///
+    /// The encoding is the number of actions as a little-endian `u32`, followed by the
+    /// witnesses and then the public inputs of each Action circuit.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let len = u32::try_from(self.circuits.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Too many actions"))?;
+        writer.write_all(&len.to_le_bytes())?;
+        for (circuit, instance) in self.circuits.iter().zip(self.instances.iter()) {
+            write_circuit(circuit, &mut writer)?;
+            write_instance(instance, &mut writer)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a request written by [`ProvingRequest::write`].
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let len = u32::from_le_bytes(read_bytes(&mut reader)?);
+
+        // Each action is more than 1 KiB, so we don't pre-allocate for an untrusted
+        // count.
+        let mut circuits = vec![];
+        let mut instances = vec![];
+        for _ in 0..len {
+            circuits.push(read_circuit(&mut reader)?);
+            instances.push(read_instance(&mut reader)?);
+        }
+
+        Ok(ProvingRequest {
+            circuits,
+            instances,
+        })
+    }
+}
+
+impl Proof {
+    /// Creates a proof for the Action circuits in the given request.
+    pub fn create_from_request(
+        pk: &ProvingKey,
+        request: &ProvingRequest,
+        rng: impl RngCore,
+    ) -> Result<Self, plonk::Error> {
+        Proof::create(pk, &request.circuits, &request.instances, rng)
+    }
+}
@@ -0,0 +1,1108 @@
+//! A proof-of-reserves circuit for Orchard notes.
+//!
+//! A [`ReservesProof`] shows that its creator can spend a set of unspent notes, committed
+//! to under a public anchor, whose total value is at least some threshold, without
+//! revealing the notes themselves. It is built from the same gadgets as the Action
+//! circuit: each note is proven by a spend-only circuit that checks the note's
+//! commitment, its address, its Merkle path to the anchor, and its nullifier, and that
+//! publishes a value commitment to the note's value and a randomized validating key.
+//!
+//! The proof does not reveal the nullifiers of the notes. Instead, each note publishes a
+//! [`ReservesTag`] derived in-circuit from its nullifier, the anchor, and the verifier's
+//! challenge. The verifier confirms that the notes are unspent by deriving the tags of
+//! the nullifiers in the chain's nullifier set. The notes can still be spent afterwards,
+//! but a verifier that keeps the proof can link those spends to it.
+//!
+//! The total value is revealed, and bound to the per-note value commitments with a
+//! binding signature in the same way as a bundle's value balance. Each note is also
+//! signed by its randomized spend authorizing key, so that holding a full viewing key is
+//! not enough to create a proof.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use blake2b_simd::Params;
+use ff::{Field, PrimeField};
+use group::{Curve, GroupEncoding};
+use halo2_proofs::{
+    circuit::{floor_planner, Layouter},
+    plonk::{self, SingleVerifier},
+    transcript::{Blake2bRead, Blake2bWrite},
+};
+use pasta_curves::{arithmetic::CurveAffine, pallas, vesta};
+use rand::{CryptoRng, RngCore};
+use subtle::CtOption;
+
+use super::{
+    gadget::{self, assign_free_advice},
+    request::{invalid, read_32, read_bytes},
+    Circuit, Config, Proof, K,
+};
+use crate::{
+    constants::{OrchardFixedBasesFull, OrchardHashDomains, MERKLE_DEPTH_ORCHARD},
+    keys::{
+        CommitIvkRandomness, DiversifiedTransmissionKey, FullViewingKey, NullifierDerivingKey,
+        SpendAuthorizingKey, SpendValidatingKey, SpendingKey,
+    },
+    note::{
+        commitment::{NoteCommitTrapdoor, NoteCommitment},
+        nullifier::Nullifier,
+        Note,
+    },
+    primitives::redpallas::{self, Binding, SpendAuth, VerificationKey},
+    spec::{to_base, NonIdentityPallasPoint},
+    tree::{Anchor, MerkleHashOrchard, MerklePath},
+    value::{NoteValue, OverflowError, ValueCommitTrapdoor, ValueCommitment, ValueSum},
+};
+use halo2_gadgets::{
+    ecc::{FixedPoint, NonIdentityPoint, Point, ScalarFixed, ScalarFixedShort, ScalarVar},
+    poseidon::{
+        primitives::{self as poseidon, ConstantLength},
+        Hash as PoseidonHash,
+    },
+    sinsemilla::{chip::SinsemillaChip, merkle::MerklePath as GadgetMerklePath},
+};
+
+// Absolute offsets for public inputs.
+const ANCHOR: usize = 0;
+const CV_X: usize = 1;
+const CV_Y: usize = 2;
+const TAG: usize = 3;
+const RK_X: usize = 4;
+const RK_Y: usize = 5;
+const CHALLENGE: usize = 6;
+
+const RESERVES_SIGHASH_PERSONALIZATION: &[u8; 16] = b"ZcashOrchardPoR_";
+const RESERVES_CHALLENGE_PERSONALIZATION: &[u8; 16] = b"ZcashOrchardPoRC";
+
+/// A tag that identifies a note within a [`ReservesProof`], without revealing its
+/// nullifier.
+///
+/// The tag of a note with nullifier $\mathsf{nf}$ is
+/// $\mathsf{Poseidon}(\mathsf{nf}, \mathsf{rt}, c)$, where $\mathsf{rt}$ is the anchor
+/// and $c$ is derived from the verifier's challenge. Hashing three elements separates
+/// tags from $\mathsf{PRF^{nf}}$, which hashes two. Tags for different challenges or
+/// anchors are unlinkable, but anyone who knows a nullifier can derive its tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReservesTag(pallas::Base);
+
+impl ReservesTag {
+    /// Derives the tag of the note with nullifier `nf`, in a proof under `anchor` for
+    /// the given `challenge`.
+    pub fn derive(nf: &Nullifier, anchor: &Anchor, challenge: &[u8]) -> Self {
+        ReservesTag::from_challenge(nf, anchor, challenge_base(challenge))
+    }
+
+    fn from_challenge(nf: &Nullifier, anchor: &Anchor, challenge: pallas::Base) -> Self {
+        ReservesTag(
+            poseidon::Hash::<_, poseidon::P128Pow5T3, ConstantLength<3>, 3, 2>::init().hash([
+                nf.0,
+                anchor.inner(),
+                challenge,
+            ]),
+        )
+    }
+
+    /// Parses a tag from its canonical byte representation.
+    pub fn from_bytes(bytes: &[u8; 32]) -> CtOption<Self> {
+        pallas::Base::from_repr(*bytes).map(ReservesTag)
+    }
+
+    /// Serializes this tag to its canonical byte representation.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_repr()
+    }
+}
+
+/// Maps a verifier's challenge to the field element $c$ that is a public input to every
+/// [`ReservesCircuit`] in a proof.
+fn challenge_base(challenge: &[u8]) -> pallas::Base {
+    let h = Params::new()
+        .hash_length(64)
+        .personal(RESERVES_CHALLENGE_PERSONALIZATION)
+        .hash(challenge);
+    to_base(h.as_bytes().try_into().unwrap())
+}
+
+/// The circuit proving that a single note is held and unspent.
+///
+/// This uses the configuration of the Action [`Circuit`], and omits the output note.
+#[derive(Clone, Debug, Default)]
+pub struct ReservesCircuit {
+    pub(crate) path: Option<[MerkleHashOrchard; MERKLE_DEPTH_ORCHARD]>,
+    pub(crate) pos: Option<u32>,
+    pub(crate) g_d: Option<NonIdentityPallasPoint>,
+    pub(crate) pk_d: Option<DiversifiedTransmissionKey>,
+    pub(crate) v: Option<NoteValue>,
+    pub(crate) rho: Option<Nullifier>,
+    pub(crate) psi: Option<pallas::Base>,
+    pub(crate) rcm: Option<NoteCommitTrapdoor>,
+    pub(crate) cm: Option<NoteCommitment>,
+    pub(crate) alpha: Option<pallas::Scalar>,
+    pub(crate) ak: Option<SpendValidatingKey>,
+    pub(crate) nk: Option<NullifierDerivingKey>,
+    pub(crate) rivk: Option<CommitIvkRandomness>,
+    pub(crate) rcv: Option<ValueCommitTrapdoor>,
+}
+
+impl plonk::Circuit<pallas::Base> for ReservesCircuit {
+    type Config = Config;
+    type FloorPlanner = floor_planner::V1;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut plonk::ConstraintSystem<pallas::Base>) -> Self::Config {
+        <Circuit as plonk::Circuit<pallas::Base>>::configure(meta)
+    }
+
+    #[allow(non_snake_case)]
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<pallas::Base>,
+    ) -> Result<(), plonk::Error> {
+        // Load the Sinsemilla generator lookup table used by the whole circuit.
+        SinsemillaChip::load(config.sinsemilla_config_1.clone(), &mut layouter)?;
+
+        // Construct the ECC chip.
+        let ecc_chip = config.ecc_chip();
+
+        // Witness private inputs that are used across multiple checks.
+        let psi = assign_free_advice(
+            layouter.namespace(|| "witness psi"),
+            config.advices[0],
+            self.psi,
+        )?;
+        let rho = assign_free_advice(
+            layouter.namespace(|| "witness rho"),
+            config.advices[0],
+            self.rho.map(|rho| rho.0),
+        )?;
+        let cm = Point::new(
+            ecc_chip.clone(),
+            layouter.namespace(|| "cm"),
+            self.cm.as_ref().map(|cm| cm.inner().to_affine()),
+        )?;
+        let g_d = NonIdentityPoint::new(
+            ecc_chip.clone(),
+            layouter.namespace(|| "gd"),
+            self.g_d.as_ref().map(|gd| gd.to_affine()),
+        )?;
+        let ak_P: Option<pallas::Point> = self.ak.as_ref().map(|ak| ak.into());
+        let ak_P = NonIdentityPoint::new(
+            ecc_chip.clone(),
+            layouter.namespace(|| "witness ak_P"),
+            ak_P.map(|ak_P| ak_P.to_affine()),
+        )?;
+        let nk = assign_free_advice(
+            layouter.namespace(|| "witness nk"),
+            config.advices[0],
+            self.nk.as_ref().map(|nk| nk.inner()),
+        )?;
+        let v = assign_free_advice(
+            layouter.namespace(|| "witness v"),
+            config.advices[0],
+            self.v,
+        )?;
+
+        // Merkle path validity check. Unlike in the Action circuit, the root must equal
+        // the anchor even for notes of zero value.
+        let root = {
+            let path = self
+                .path
+                .map(|typed_path| typed_path.map(|node| node.inner()));
+            let merkle_inputs = GadgetMerklePath::construct(
+                [config.merkle_chip_1(), config.merkle_chip_2()],
+                OrchardHashDomains::MerkleCrh,
+                self.pos,
+                path,
+            );
+            let leaf = cm.extract_p().inner().clone();
+            let root = merkle_inputs.calculate_root(layouter.namespace(|| "Merkle path"), leaf)?;
+
+            layouter.constrain_instance(root.cell(), config.primary, ANCHOR)?;
+            root
+        };
+
+        // Value commitment integrity. The committed value is the note's value, with its
+        // sign fixed to be positive.
+        {
+            let (magnitude, sign) = layouter.assign_region(
+                || "v magnitude and sign",
+                |mut region| {
+                    let magnitude = region.assign_advice(
+                        || "v magnitude",
+                        config.advices[9],
+                        0,
+                        || {
+                            self.v
+                                .map(|v| pallas::Base::from(v.inner()))
+                                .ok_or(plonk::Error::Synthesis)
+                        },
+                    )?;
+                    region.constrain_equal(v.cell(), magnitude.cell())?;
+                    let sign = region.assign_advice_from_constant(
+                        || "v sign",
+                        config.advices[9],
+                        1,
+                        pallas::Base::one(),
+                    )?;
+                    Ok((magnitude, sign))
+                },
+            )?;
+
+            let v = ScalarFixedShort::new(
+                ecc_chip.clone(),
+                layouter.namespace(|| "v"),
+                (magnitude, sign),
+            )?;
+            let rcv = ScalarFixed::new(
+                ecc_chip.clone(),
+                layouter.namespace(|| "rcv"),
+                self.rcv.as_ref().map(|rcv| rcv.inner()),
+            )?;
+
+            let cv = gadget::value_commit_orchard(
+                layouter.namespace(|| "cv = ValueCommit^Orchard_rcv(v)"),
+                ecc_chip.clone(),
+                v,
+                rcv,
+            )?;
+
+            // Constrain cv to equal public input
+            layouter.constrain_instance(cv.inner().x().cell(), config.primary, CV_X)?;
+            layouter.constrain_instance(cv.inner().y().cell(), config.primary, CV_Y)?;
+        }
+
+        // Tag integrity
+        {
+            let nf = gadget::derive_nullifier(
+                layouter.namespace(|| "nf = DeriveNullifier_nk(rho, psi, cm)"),
+                config.poseidon_chip(),
+                config.add_chip(),
+                ecc_chip.clone(),
+                rho.clone(),
+                &psi,
+                &cm,
+                nk.clone(),
+            )?;
+
+            let challenge = layouter.assign_region(
+                || "challenge",
+                |mut region| {
+                    region.assign_advice_from_instance(
+                        || "pub input challenge",
+                        config.primary,
+                        CHALLENGE,
+                        config.advices[0],
+                        0,
+                    )
+                },
+            )?;
+
+            // tag = poseidon_hash(nf, rt, c)
+            let tag = {
+                let poseidon_message = [nf.inner().clone(), root, challenge];
+                let poseidon_hasher =
+                    PoseidonHash::<_, _, poseidon::P128Pow5T3, ConstantLength<3>, 3, 2>::init(
+                        config.poseidon_chip(),
+                        layouter.namespace(|| "Poseidon init"),
+                    )?;
+                poseidon_hasher.hash(
+                    layouter.namespace(|| "Poseidon hash (nf, rt, c)"),
+                    poseidon_message,
+                )?
+            };
+
+            // Constrain tag to equal public input
+            layouter.constrain_instance(tag.cell(), config.primary, TAG)?;
+        }
+
+        // Spend authority
+        {
+            let alpha =
+                ScalarFixed::new(ecc_chip.clone(), layouter.namespace(|| "alpha"), self.alpha)?;
+
+            // alpha_commitment = [alpha] SpendAuthG
+            let (alpha_commitment, _) = {
+                let spend_auth_g = OrchardFixedBasesFull::SpendAuthG;
+                let spend_auth_g = FixedPoint::from_inner(ecc_chip.clone(), spend_auth_g);
+                spend_auth_g.mul(layouter.namespace(|| "[alpha] SpendAuthG"), alpha)?
+            };
+
+            // [alpha] SpendAuthG + ak_P
+            let rk = alpha_commitment.add(layouter.namespace(|| "rk"), &ak_P)?;
+
+            // Constrain rk to equal public input
+            layouter.constrain_instance(rk.inner().x().cell(), config.primary, RK_X)?;
+            layouter.constrain_instance(rk.inner().y().cell(), config.primary, RK_Y)?;
+        }
+
+        // Diversified address integrity.
+        let pk_d = {
+            let ivk = {
+                let ak = ak_P.extract_p().inner().clone();
+                let rivk = ScalarFixed::new(
+                    ecc_chip.clone(),
+                    layouter.namespace(|| "rivk"),
+                    self.rivk.as_ref().map(|rivk| rivk.inner()),
+                )?;
+
+                gadget::commit_ivk(
+                    config.sinsemilla_chip_1(),
+                    ecc_chip.clone(),
+                    config.commit_ivk_chip(),
+                    layouter.namespace(|| "CommitIvk"),
+                    ak,
+                    nk,
+                    rivk,
+                )?
+            };
+            let ivk =
+                ScalarVar::from_base(ecc_chip.clone(), layouter.namespace(|| "ivk"), ivk.inner())?;
+
+            // [ivk] g_d
+            let (derived_pk_d, _ivk) = g_d.mul(layouter.namespace(|| "[ivk] g_d"), ivk)?;
+
+            let pk_d = NonIdentityPoint::new(
+                ecc_chip.clone(),
+                layouter.namespace(|| "witness pk_d"),
+                self.pk_d.map(|pk_d| pk_d.inner().to_affine()),
+            )?;
+            derived_pk_d.constrain_equal(layouter.namespace(|| "pk_d equality"), &pk_d)?;
+
+            pk_d
+        };
+
+        // Note commitment integrity.
+        {
+            let rcm = ScalarFixed::new(
+                ecc_chip,
+                layouter.namespace(|| "rcm"),
+                self.rcm.as_ref().map(|rcm| rcm.inner()),
+            )?;
+
+            // g★_d || pk★_d || i2lebsp_{64}(v) || i2lebsp_{255}(rho) || i2lebsp_{255}(psi)
+            let derived_cm = gadget::note_commit(
+                layouter.namespace(|| {
+                    "g★_d || pk★_d || i2lebsp_{64}(v) || i2lebsp_{255}(rho) || i2lebsp_{255}(psi)"
+                }),
+                config.sinsemilla_chip_1(),
+                config.ecc_chip(),
+                config.note_commit_chip_old(),
+                g_d.inner(),
+                pk_d.inner(),
+                v,
+                rho,
+                psi,
+                rcm,
+            )?;
+
+            // Constrain derived cm to equal witnessed cm
+            derived_cm.constrain_equal(layouter.namespace(|| "cm equality"), &cm)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Public inputs to the [`ReservesCircuit`] for a single note.
+///
+/// The circuit also takes the verifier's challenge as a public input, which is shared by
+/// every note in a proof.
+#[derive(Clone, Debug)]
+pub struct ReservesInstance {
+    pub(crate) anchor: Anchor,
+    pub(crate) cv: ValueCommitment,
+    pub(crate) tag: ReservesTag,
+    pub(crate) rk: VerificationKey<SpendAuth>,
+}
+
+impl ReservesInstance {
+    /// Constructs a [`ReservesInstance`] from its constituent parts.
+    pub fn from_parts(
+        anchor: Anchor,
+        cv: ValueCommitment,
+        tag: ReservesTag,
+        rk: VerificationKey<SpendAuth>,
+    ) -> Self {
+        ReservesInstance {
+            anchor,
+            cv,
+            tag,
+            rk,
+        }
+    }
+
+    /// Returns the commitment to the value of the note.
+    pub fn cv(&self) -> &ValueCommitment {
+        &self.cv
+    }
+
+    /// Returns the tag of the note.
+    pub fn tag(&self) -> &ReservesTag {
+        &self.tag
+    }
+
+    /// Returns the randomized validating key for the note.
+    pub fn rk(&self) -> &VerificationKey<SpendAuth> {
+        &self.rk
+    }
+
+    fn to_halo2_instance(&self, challenge: pallas::Base) -> [[vesta::Scalar; 7]; 1] {
+        let mut instance = [vesta::Scalar::zero(); 7];
+
+        instance[ANCHOR] = self.anchor.inner();
+        instance[CV_X] = self.cv.x();
+        instance[CV_Y] = self.cv.y();
+        instance[TAG] = self.tag.0;
+        instance[CHALLENGE] = challenge;
+
+        let rk = pallas::Point::from_bytes(&self.rk.clone().into())
+            .unwrap()
+            .to_affine()
+            .coordinates()
+            .unwrap();
+
+        instance[RK_X] = *rk.x();
+        instance[RK_Y] = *rk.y();
+
+        [instance]
+    }
+}
+
+/// The verifying key for the [`ReservesCircuit`].
+#[derive(Debug)]
+pub struct ReservesVerifyingKey {
+    params: halo2_proofs::poly::commitment::Params<vesta::Affine>,
+    vk: plonk::VerifyingKey<vesta::Affine>,
+}
+
+impl ReservesVerifyingKey {
+    /// Builds the verifying key.
+    pub fn build() -> Self {
+        let params = halo2_proofs::poly::commitment::Params::new(K);
+        let circuit: ReservesCircuit = Default::default();
+
+        let vk = plonk::keygen_vk(&params, &circuit).unwrap();
+
+        ReservesVerifyingKey { params, vk }
+    }
+}
+
+/// The proving key for the [`ReservesCircuit`].
+#[derive(Debug)]
+pub struct ReservesProvingKey {
+    params: halo2_proofs::poly::commitment::Params<vesta::Affine>,
+    pk: plonk::ProvingKey<vesta::Affine>,
+}
+
+impl ReservesProvingKey {
+    /// Builds the proving key.
+    pub fn build() -> Self {
+        let params = halo2_proofs::poly::commitment::Params::new(K);
+        let circuit: ReservesCircuit = Default::default();
+
+        let vk = plonk::keygen_vk(&params, &circuit).unwrap();
+        let pk = plonk::keygen_pk(&params, vk, &circuit).unwrap();
+
+        ReservesProvingKey { params, pk }
+    }
+}
+
+/// Errors that can occur when creating or verifying a [`ReservesProof`].
+#[derive(Debug)]
+pub enum Error {
+    /// No notes were given.
+    NoNotes,
+    /// A note's Merkle path does not lead to the anchor of the other notes, or the
+    /// proof is for a different anchor than the one expected.
+    AnchorMismatch,
+    /// A note was not sent to an address of the spending key given for it.
+    WrongSpendingKey,
+    /// The total value of the notes overflowed.
+    ValueSum(OverflowError),
+    /// The same tag appears more than once.
+    DuplicateTag,
+    /// The proof does not have one spend authorization signature for each note.
+    SignatureCountMismatch,
+    /// The total value of the notes is less than the required threshold.
+    BelowThreshold,
+    /// A spend authorization signature or the binding signature is invalid.
+    InvalidSignature,
+    /// An error occurred while creating or verifying the proof.
+    Proof(plonk::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoNotes => f.write_str("No notes were given"),
+            Error::AnchorMismatch => f.write_str("Notes do not share the expected anchor"),
+            Error::WrongSpendingKey => {
+                f.write_str("Note was not sent to an address of its spending key")
+            }
+            Error::ValueSum(_) => f.write_str("Total value overflowed"),
+            Error::DuplicateTag => f.write_str("Tag appears more than once"),
+            Error::SignatureCountMismatch => {
+                f.write_str("Number of signatures does not match number of notes")
+            }
+            Error::BelowThreshold => f.write_str("Total value is below the threshold"),
+            Error::InvalidSignature => f.write_str("Invalid signature"),
+            Error::Proof(e) => write!(f, "Proof error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<plonk::Error> for Error {
+    fn from(e: plonk::Error) -> Self {
+        Error::Proof(e)
+    }
+}
+
+impl From<OverflowError> for Error {
+    fn from(e: OverflowError) -> Self {
+        Error::ValueSum(e)
+    }
+}
+
+/// A proof that a set of unspent notes with a given total value is held.
+#[derive(Clone, Debug)]
+pub struct ReservesProof {
+    anchor: Anchor,
+    instances: Vec<ReservesInstance>,
+    total: NoteValue,
+    proof: Proof,
+    spend_auth_sigs: Vec<redpallas::Signature<SpendAuth>>,
+    binding_signature: redpallas::Signature<Binding>,
+}
+
+impl ReservesProof {
+    /// Creates a proof of reserves for the given notes.
+    ///
+    /// Each note is given with a spending key for its address, and a Merkle path to the
+    /// same anchor. `challenge` is chosen by the verifier, and prevents the proof from
+    /// being replayed to other verifiers.
+    pub fn create<R: RngCore + CryptoRng>(
+        pk: &ReservesProvingKey,
+        notes: &[(SpendingKey, Note, MerklePath)],
+        challenge: &[u8],
+        mut rng: R,
+    ) -> Result<Self, Error> {
+        let anchor = match notes.first() {
+            Some((_, note, merkle_path)) => merkle_path.root(note.commitment().into()),
+            None => return Err(Error::NoNotes),
+        };
+
+        let total = notes
+            .iter()
+            .try_fold(0u64, |acc, (_, note, _)| {
+                acc.checked_add(note.value().inner())
+            })
+            .filter(|total| i64::try_from(*total).is_ok())
+            .ok_or(OverflowError)?;
+
+        let c = challenge_base(challenge);
+        let mut circuits = Vec::with_capacity(notes.len());
+        let mut instances = Vec::with_capacity(notes.len());
+        let mut signing = Vec::with_capacity(notes.len());
+        let mut rcvs = Vec::with_capacity(notes.len());
+        for (sk, note, merkle_path) in notes {
+            let (circuit, instance, rsk, rcv) =
+                prepare_note(sk, note, merkle_path, anchor, c, &mut rng)?;
+            circuits.push(circuit);
+            instances.push(instance);
+            signing.push(rsk);
+            rcvs.push(rcv);
+        }
+
+        let halo2_instances: Vec<_> = instances.iter().map(|i| i.to_halo2_instance(c)).collect();
+        let halo2_instances: Vec<Vec<_>> = halo2_instances
+            .iter()
+            .map(|i| i.iter().map(|c| &c[..]).collect())
+            .collect();
+        let halo2_instances: Vec<_> = halo2_instances.iter().map(|i| &i[..]).collect();
+
+        let mut transcript = Blake2bWrite::<_, vesta::Affine, _>::init(vec![]);
+        plonk::create_proof(
+            &pk.params,
+            &pk.pk,
+            &circuits,
+            &halo2_instances,
+            &mut rng,
+            &mut transcript,
+        )?;
+        let proof = Proof::new(transcript.finalize());
+
+        let total = NoteValue::from_raw(total);
+        let sighash = reserves_sighash(&anchor, total, &instances, challenge);
+        let spend_auth_sigs = signing
+            .iter()
+            .map(|rsk| rsk.sign(&mut rng, &sighash))
+            .collect();
+        let bsk = rcvs.iter().sum::<ValueCommitTrapdoor>().into_bsk();
+        let binding_signature = bsk.sign(&mut rng, &sighash);
+
+        Ok(ReservesProof {
+            anchor,
+            instances,
+            total,
+            proof,
+            spend_auth_sigs,
+            binding_signature,
+        })
+    }
+
+    /// Verifies that this proof shows reserves of at least `threshold` under `anchor`,
+    /// for the given `challenge`.
+    ///
+    /// This does not check that the notes are unspent. The caller must check, with
+    /// [`ReservesProof::contains_nullifier`], that none of the nullifiers in the
+    /// nullifier set of the chain belongs to a note of this proof.
+    pub fn verify(
+        &self,
+        vk: &ReservesVerifyingKey,
+        anchor: &Anchor,
+        threshold: NoteValue,
+        challenge: &[u8],
+    ) -> Result<(), Error> {
+        if self.instances.is_empty() {
+            return Err(Error::NoNotes);
+        }
+        if self.instances.len() != self.spend_auth_sigs.len() {
+            return Err(Error::SignatureCountMismatch);
+        }
+        if &self.anchor != anchor || self.instances.iter().any(|i| &i.anchor != anchor) {
+            return Err(Error::AnchorMismatch);
+        }
+        if self.total.inner() < threshold.inner() {
+            return Err(Error::BelowThreshold);
+        }
+        let mut tags = BTreeSet::new();
+        if !self.instances.iter().all(|i| tags.insert(i.tag.to_bytes())) {
+            return Err(Error::DuplicateTag);
+        }
+
+        let c = challenge_base(challenge);
+        let halo2_instances: Vec<_> = self
+            .instances
+            .iter()
+            .map(|i| i.to_halo2_instance(c))
+            .collect();
+        let halo2_instances: Vec<Vec<_>> = halo2_instances
+            .iter()
+            .map(|i| i.iter().map(|c| &c[..]).collect())
+            .collect();
+        let halo2_instances: Vec<_> = halo2_instances.iter().map(|i| &i[..]).collect();
+
+        let strategy = SingleVerifier::new(&vk.params);
+        let mut transcript = Blake2bRead::init(self.proof.as_ref());
+        plonk::verify_proof(
+            &vk.params,
+            &vk.vk,
+            strategy,
+            &halo2_instances,
+            &mut transcript,
+        )?;
+
+        let total = i64::try_from(self.total.inner()).map_err(|_| OverflowError)?;
+        let sighash = reserves_sighash(&self.anchor, self.total, &self.instances, challenge);
+        for (instance, sig) in self.instances.iter().zip(self.spend_auth_sigs.iter()) {
+            instance
+                .rk
+                .verify(&sighash, sig)
+                .map_err(|_| Error::InvalidSignature)?;
+        }
+        self.binding_validating_key(total)
+            .verify(&sighash, &self.binding_signature)
+            .map_err(|_| Error::InvalidSignature)
+    }
+
+    /// Returns the anchor that the notes are committed to under.
+    pub fn anchor(&self) -> &Anchor {
+        &self.anchor
+    }
+
+    /// Returns the total value of the notes.
+    pub fn total(&self) -> NoteValue {
+        self.total
+    }
+
+    /// Returns the public inputs for each note.
+    pub fn instances(&self) -> &[ReservesInstance] {
+        &self.instances
+    }
+
+    /// Returns the tags of the notes.
+    pub fn tags(&self) -> impl Iterator<Item = &ReservesTag> {
+        self.instances.iter().map(|i| &i.tag)
+    }
+
+    /// Returns whether the note with nullifier `nf` is one of the notes of this proof,
+    /// which was created for the given `challenge`.
+    pub fn contains_nullifier(&self, nf: &Nullifier, challenge: &[u8]) -> bool {
+        let tag = ReservesTag::derive(nf, &self.anchor, challenge);
+        self.tags().any(|t| *t == tag)
+    }
+
+    /// Returns the proof of the [`ReservesCircuit`] for every note.
+    pub fn proof(&self) -> &Proof {
+        &self.proof
+    }
+
+    /// Writes this proof.
+    ///
+    /// The encoding is the anchor, the total value as a little-endian `u64`, and the
+    /// number of notes as a little-endian `u32`. For each note it has the value
+    /// commitment, tag, randomized validating key and spend authorization signature.
+    /// These are followed by the length of the proof as a little-endian `u32`, the
+    /// proof, and the binding signature. The anchor of every note is the anchor of the
+    /// proof, and is not repeated.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        if self.instances.len() != self.spend_auth_sigs.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Number of signatures does not match number of notes",
+            ));
+        }
+        let len = u32::try_from(self.instances.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Too many notes"))?;
+        let proof_len = u32::try_from(self.proof.as_ref().len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Proof is too large"))?;
+
+        writer.write_all(&self.anchor.to_bytes())?;
+        writer.write_all(&self.total.to_bytes())?;
+        writer.write_all(&len.to_le_bytes())?;
+        for (instance, sig) in self.instances.iter().zip(self.spend_auth_sigs.iter()) {
+            writer.write_all(&instance.cv.to_bytes())?;
+            writer.write_all(&instance.tag.to_bytes())?;
+            writer.write_all(&<[u8; 32]>::from(&instance.rk))?;
+            writer.write_all(&<[u8; 64]>::from(sig))?;
+        }
+        writer.write_all(&proof_len.to_le_bytes())?;
+        writer.write_all(self.proof.as_ref())?;
+        writer.write_all(&<[u8; 64]>::from(&self.binding_signature))
+    }
+
+    /// Reads a proof written by [`ReservesProof::write`].
+    ///
+    /// This only checks that the encoding is canonical. The proof must still be checked
+    /// with [`ReservesProof::verify`].
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let anchor = read_32(&mut reader, |b| Anchor::from_bytes(*b), "Invalid anchor")?;
+        let total = NoteValue::from_bytes(read_bytes(&mut reader)?);
+        let len = u32::from_le_bytes(read_bytes(&mut reader)?);
+
+        // We don't pre-allocate for an untrusted count.
+        let mut instances = vec![];
+        let mut spend_auth_sigs = vec![];
+        for _ in 0..len {
+            let cv = read_32(&mut reader, ValueCommitment::from_bytes, "Invalid cv")?;
+            let tag = read_32(&mut reader, ReservesTag::from_bytes, "Invalid tag")?;
+            let rk = VerificationKey::try_from(read_bytes::<_, 32>(&mut reader)?)
+                .map_err(|_| invalid("Invalid rk"))?;
+            instances.push(ReservesInstance::from_parts(anchor, cv, tag, rk));
+            spend_auth_sigs.push(redpallas::Signature::from(read_bytes(&mut reader)?));
+        }
+
+        let proof_len = u32::from_le_bytes(read_bytes(&mut reader)?);
+        let mut proof = vec![];
+        (&mut reader)
+            .take(u64::from(proof_len))
+            .read_to_end(&mut proof)?;
+        if proof.len() != proof_len as usize {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let binding_signature = redpallas::Signature::from(read_bytes(&mut reader)?);
+
+        Ok(ReservesProof {
+            anchor,
+            instances,
+            total,
+            proof: Proof::new(proof),
+            spend_auth_sigs,
+            binding_signature,
+        })
+    }
+
+    /// Returns the key that validates the binding signature, derived from the value
+    /// commitments of the notes and their claimed total.
+    fn binding_validating_key(&self, total: i64) -> redpallas::VerificationKey<Binding> {
+        (self
+            .instances
+            .iter()
+            .map(|i| &i.cv)
+            .sum::<ValueCommitment>()
+            - ValueCommitment::derive(ValueSum::from_raw(total), ValueCommitTrapdoor::zero()))
+        .into_bvk()
+    }
+}
+
+/// Builds the circuit and public inputs for a single note, and returns them along with
+/// the randomized spend authorizing key and value commitment trapdoor for the note.
+fn prepare_note(
+    sk: &SpendingKey,
+    note: &Note,
+    merkle_path: &MerklePath,
+    anchor: Anchor,
+    challenge: pallas::Base,
+    mut rng: impl RngCore,
+) -> Result<
+    (
+        ReservesCircuit,
+        ReservesInstance,
+        redpallas::SigningKey<SpendAuth>,
+        ValueCommitTrapdoor,
+    ),
+    Error,
+> {
+    if merkle_path.root(note.commitment().into()) != anchor {
+        return Err(Error::AnchorMismatch);
+    }
+    let fvk = FullViewingKey::from(sk);
+    let scope = fvk
+        .scope_for_address(&note.recipient())
+        .ok_or(Error::WrongSpendingKey)?;
+
+    let rho = note.rho();
+    let ak: SpendValidatingKey = fvk.clone().into();
+    let alpha = pallas::Scalar::random(&mut rng);
+    let rcv = ValueCommitTrapdoor::random(&mut rng);
+    let cv = ValueCommitment::derive(note.value() - NoteValue::zero(), rcv.clone());
+
+    let instance = ReservesInstance {
+        anchor,
+        cv,
+        tag: ReservesTag::from_challenge(&note.nullifier(&fvk), &anchor, challenge),
+        rk: ak.randomize(&alpha),
+    };
+    let circuit = ReservesCircuit {
+        path: Some(merkle_path.auth_path()),
+        pos: Some(merkle_path.position()),
+        g_d: Some(note.recipient().g_d()),
+        pk_d: Some(*note.recipient().pk_d()),
+        v: Some(note.value()),
+        rho: Some(rho),
+        psi: Some(note.rseed().psi(&rho)),
+        rcm: Some(note.rseed().rcm(&rho)),
+        cm: Some(note.commitment()),
+        alpha: Some(alpha),
+        ak: Some(ak),
+        nk: Some(fvk.nk().clone()),
+        rivk: Some(fvk.rivk(scope)),
+        rcv: Some(rcv.clone()),
+    };
+    let rsk = SpendAuthorizingKey::from(sk).randomize(&alpha);
+
+    Ok((circuit, instance, rsk, rcv))
+}
+
+/// Computes the message signed by a [`ReservesProof`].
+fn reserves_sighash(
+    anchor: &Anchor,
+    total: NoteValue,
+    instances: &[ReservesInstance],
+    challenge: &[u8],
+) -> [u8; 32] {
+    let mut h = Params::new()
+        .hash_length(32)
+        .personal(RESERVES_SIGHASH_PERSONALIZATION)
+        .to_state();
+    h.update(&(challenge.len() as u64).to_le_bytes());
+    h.update(challenge);
+    h.update(&anchor.to_bytes());
+    h.update(&total.to_bytes());
+    for instance in instances {
+        h.update(&instance.cv.to_bytes());
+        h.update(&instance.tag.to_bytes());
+        h.update(&<[u8; 32]>::from(&instance.rk));
+    }
+    h.finalize().as_bytes().try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::dev::MockProver;
+    use rand::rngs::OsRng;
+
+    use super::{Error, ReservesProof, ReservesProvingKey, ReservesVerifyingKey};
+    use crate::{
+        constants::MERKLE_DEPTH_ORCHARD,
+        keys::{FullViewingKey, Scope, SpendingKey},
+        note::{ExtractedNoteCommitment, Note, Nullifier},
+        tree::{MerkleHashOrchard, MerklePath, EMPTY_ROOTS},
+        value::NoteValue,
+    };
+
+    /// Returns the Merkle path of the leaf at `position` in a tree containing only the
+    /// given two leaves.
+    fn two_leaf_path(position: u32, sibling: &Note) -> MerklePath {
+        let mut auth_path: [MerkleHashOrchard; MERKLE_DEPTH_ORCHARD] =
+            EMPTY_ROOTS[..MERKLE_DEPTH_ORCHARD].try_into().unwrap();
+        auth_path[0] =
+            MerkleHashOrchard::from_cmx(&ExtractedNoteCommitment::from(sibling.commitment()));
+        MerklePath::from_parts(position, auth_path)
+    }
+
+    #[test]
+    fn reserves_round_trip() {
+        let mut rng = OsRng;
+
+        let sk = SpendingKey::random(&mut rng);
+        let fvk = FullViewingKey::from(&sk);
+        let note_a = Note::new(
+            fvk.address_at(0u32, Scope::External),
+            NoteValue::from_raw(40_000),
+            Nullifier::dummy(&mut rng),
+            &mut rng,
+        );
+        let note_b = Note::new(
+            fvk.address_at(7u32, Scope::Internal),
+            NoteValue::from_raw(25_000),
+            Nullifier::dummy(&mut rng),
+            &mut rng,
+        );
+        let path_a = two_leaf_path(0, &note_b);
+        let path_b = two_leaf_path(1, &note_a);
+        let anchor = path_a.root(note_a.commitment().into());
+        assert_eq!(anchor, path_b.root(note_b.commitment().into()));
+
+        let notes = [
+            (sk.clone(), note_a, path_a.clone()),
+            (sk.clone(), note_b, path_b),
+        ];
+
+        let pk = ReservesProvingKey::build();
+        let vk = ReservesVerifyingKey::build();
+
+        // Notes under different anchors cannot be combined.
+        let other_path = MerklePath::dummy(&mut rng);
+        assert!(matches!(
+            ReservesProof::create(
+                &pk,
+                &[notes[0].clone(), (sk, note_b, other_path)],
+                b"challenge",
+                &mut rng,
+            ),
+            Err(Error::AnchorMismatch)
+        ));
+
+        let proof = ReservesProof::create(&pk, &notes, b"challenge", &mut rng).unwrap();
+        assert_eq!(proof.total().inner(), 65_000);
+        assert_eq!(proof.tags().count(), 2);
+
+        // The verifier can recognise the notes by their nullifiers, but only for the
+        // challenge that the proof was created for.
+        let nf_a = note_a.nullifier(&fvk);
+        assert!(proof.contains_nullifier(&nf_a, b"challenge"));
+        assert!(!proof.contains_nullifier(&nf_a, b"other challenge"));
+        assert!(!proof.contains_nullifier(&Nullifier::dummy(&mut rng), b"challenge"));
+
+        assert!(proof
+            .verify(&vk, &anchor, NoteValue::from_raw(65_000), b"challenge")
+            .is_ok());
+
+        // The proof can be sent to a verifier in another process.
+        let mut encoded = vec![];
+        proof.write(&mut encoded).unwrap();
+        let received = ReservesProof::read(&encoded[..]).unwrap();
+        let mut reencoded = vec![];
+        received.write(&mut reencoded).unwrap();
+        assert_eq!(encoded, reencoded);
+        assert_eq!(received.total().inner(), 65_000);
+        assert!(received.contains_nullifier(&nf_a, b"challenge"));
+        assert!(received
+            .verify(&vk, &anchor, NoteValue::from_raw(65_000), b"challenge")
+            .is_ok());
+        assert!(ReservesProof::read(&encoded[..encoded.len() - 1]).is_err());
+
+        assert!(matches!(
+            proof.verify(&vk, &anchor, NoteValue::from_raw(65_001), b"challenge"),
+            Err(Error::BelowThreshold)
+        ));
+        assert!(matches!(
+            proof.verify(&vk, &anchor, NoteValue::from_raw(1), b"other challenge"),
+            Err(Error::InvalidSignature)
+        ));
+        let other_anchor = MerklePath::dummy(&mut rng).root(note_a.commitment().into());
+        assert!(matches!(
+            proof.verify(&vk, &other_anchor, NoteValue::from_raw(1), b"challenge"),
+            Err(Error::AnchorMismatch)
+        ));
+
+        // Overstating the total breaks the binding signature.
+        let mut inflated = proof.clone();
+        inflated.total = NoteValue::from_raw(100_000);
+        assert!(matches!(
+            inflated.verify(&vk, &anchor, NoteValue::from_raw(1), b"challenge"),
+            Err(Error::InvalidSignature)
+        ));
+
+        // The same note cannot be counted twice.
+        let mut doubled = proof.clone();
+        doubled.instances[1] = doubled.instances[0].clone();
+        assert!(matches!(
+            doubled.verify(&vk, &anchor, NoteValue::from_raw(1), b"challenge"),
+            Err(Error::DuplicateTag)
+        ));
+
+        // Every note must be signed.
+        let mut unsigned = proof.clone();
+        unsigned.spend_auth_sigs.pop();
+        assert!(matches!(
+            unsigned.verify(&vk, &anchor, NoteValue::from_raw(1), b"challenge"),
+            Err(Error::SignatureCountMismatch)
+        ));
+
+        // A key that did not receive the notes cannot prove them.
+        let stranger = SpendingKey::random(&mut rng);
+        assert!(matches!(
+            ReservesProof::create(&pk, &[(stranger, note_a, path_a)], b"challenge", &mut rng),
+            Err(Error::WrongSpendingKey)
+        ));
+    }
+
+    #[test]
+    fn reserves_circuit_checks_anchor() {
+        let mut rng = OsRng;
+
+        let (sk, _, note) = Note::dummy(&mut rng, None);
+        let path = MerklePath::dummy(&mut rng);
+        let anchor = path.root(note.commitment().into());
+
+        let challenge = super::challenge_base(b"challenge");
+        let (circuit, instance, _, _) =
+            super::prepare_note(&sk, &note, &path, anchor, challenge, &mut rng).unwrap();
+        let run = |instance: &super::ReservesInstance| {
+            MockProver::run(
+                super::K,
+                &circuit,
+                instance
+                    .to_halo2_instance(challenge)
+                    .iter()
+                    .map(|p| p.to_vec())
+                    .collect(),
+            )
+            .unwrap()
+            .verify()
+        };
+        assert_eq!(run(&instance), Ok(()));
+
+        // The root must equal the anchor, even though the Action circuit would allow
+        // a zero-valued note to have any anchor.
+        let mut other = instance.clone();
+        other.anchor = MerklePath::dummy(&mut rng).root(note.commitment().into());
+        assert!(run(&other).is_err());
+
+        // The tag must be derived from the note's nullifier.
+        let mut other = instance.clone();
+        other.tag =
+            super::ReservesTag::from_challenge(&Nullifier::dummy(&mut rng), &anchor, challenge);
+        assert!(run(&other).is_err());
+    }
+}
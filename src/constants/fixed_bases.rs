@@ -47,12 +47,17 @@ pub const NUM_WINDOWS: usize =
 pub const NUM_WINDOWS_SHORT: usize =
     (L_VALUE + FIXED_BASE_WINDOW_SIZE - 1) / FIXED_BASE_WINDOW_SIZE;
 
+/// The fixed bases used in the Orchard circuit.
+///
+/// This is a sum type for both full-width and short bases. This enables us to use the
+/// shared functionality of full-width and short fixed-base scalar multiplication.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-// A sum type for both full-width and short bases. This enables us to use the
-// shared functionality of full-width and short fixed-base scalar multiplication.
 pub enum OrchardFixedBases {
+    /// A base used with full-width scalars.
     Full(OrchardFixedBasesFull),
+    /// The base used to derive nullifiers, with a base field element as the scalar.
     NullifierK,
+    /// The base used to commit to values, with a short signed scalar.
     ValueCommitV,
 }
 
@@ -77,9 +82,13 @@ impl From<NullifierK> for OrchardFixedBases {
 /// The Orchard fixed bases used in scalar mul with full-width scalars.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum OrchardFixedBasesFull {
+    /// The randomness base of `Commit^ivk`.
     CommitIvkR,
+    /// The randomness base of `NoteCommit^Orchard`.
     NoteCommitR,
+    /// The randomness base of `ValueCommit^Orchard`.
     ValueCommitR,
+    /// The base of spend authorization signatures.
     SpendAuthG,
 }
 
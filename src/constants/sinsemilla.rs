@@ -75,10 +75,14 @@ pub(crate) fn i2lebsp_k(int: usize) -> [bool; K] {
     i2lebsp(int as u64)
 }
 
+/// The Sinsemilla hash domains used in the Orchard circuit.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum OrchardHashDomains {
+    /// The hash domain of `NoteCommit^Orchard`.
     NoteCommit,
+    /// The hash domain of `Commit^ivk`.
     CommitIvk,
+    /// The hash domain of `MerkleCRH^Orchard`.
     MerkleCrh,
 }
 
@@ -105,9 +109,12 @@ impl HashDomains<pallas::Affine> for OrchardHashDomains {
     }
 }
 
+/// The Sinsemilla commitment domains used in the Orchard circuit.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum OrchardCommitDomains {
+    /// The commitment domain of `NoteCommit^Orchard`.
     NoteCommit,
+    /// The commitment domain of `Commit^ivk`.
     CommitIvk,
 }
 
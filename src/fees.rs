@@ -0,0 +1,135 @@
+//! Fee rules for transactions containing Orchard bundles.
+
+use std::fmt::Debug;
+
+use crate::{
+    builder::MIN_ACTIONS,
+    value::{NoteValue, OverflowError},
+};
+
+/// Returns the number of ZIP 317 logical actions contributed by an Orchard bundle built
+/// from the given numbers of spends and outputs.
+///
+/// This is the number of actions in the bundle after [`Builder::build`] pads it with
+/// dummy spends and outputs, which is at least two even for a bundle with no spends or
+/// outputs. A transaction without an Orchard bundle contributes no logical actions.
+///
+/// [`Builder::build`]: crate::builder::Builder::build
+pub fn logical_actions(spends: usize, outputs: usize) -> usize {
+    spends.max(outputs).max(MIN_ACTIONS)
+}
+
+/// A rule for computing the fee required by a transaction, given the shape of its
+/// Orchard bundle.
+pub trait FeeRule: Debug {
+    /// Returns the fee required for a transaction whose Orchard bundle is built from the
+    /// given numbers of spends and outputs.
+    ///
+    /// Implementations must account for the padding applied by
+    /// [`Builder::build`](crate::builder::Builder::build).
+    fn fee_required(&self, spends: usize, outputs: usize) -> Result<NoteValue, OverflowError>;
+}
+
+/// The conventional fee rule defined in [ZIP 317: Proportional Transfer Fee
+/// Mechanism][zip317], applied to a transaction that only has Orchard components.
+///
+/// [zip317]: https://zips.z.cash/zip-0317
+#[derive(Clone, Debug)]
+pub struct Zip317FeeRule {
+    marginal_fee: u64,
+    grace_actions: usize,
+}
+
+impl Zip317FeeRule {
+    /// The ZIP 317 marginal fee per logical action, in zatoshis.
+    pub const MARGINAL_FEE: u64 = 5000;
+
+    /// The ZIP 317 number of logical actions that are covered by the minimum fee.
+    pub const GRACE_ACTIONS: usize = 2;
+
+    /// Returns the fee rule with the parameters recommended by ZIP 317.
+    pub fn standard() -> Self {
+        Zip317FeeRule {
+            marginal_fee: Self::MARGINAL_FEE,
+            grace_actions: Self::GRACE_ACTIONS,
+        }
+    }
+
+    /// Returns a fee rule with non-standard parameters.
+    ///
+    /// Transactions that pay less than the standard ZIP 317 fee may not be relayed or
+    /// mined, so this should only be used for testing or on networks with different
+    /// policies.
+    pub fn non_standard(marginal_fee: u64, grace_actions: usize) -> Self {
+        Zip317FeeRule {
+            marginal_fee,
+            grace_actions,
+        }
+    }
+
+    /// Returns the marginal fee per logical action, in zatoshis.
+    pub fn marginal_fee(&self) -> u64 {
+        self.marginal_fee
+    }
+
+    /// Returns the number of logical actions covered by the minimum fee.
+    pub fn grace_actions(&self) -> usize {
+        self.grace_actions
+    }
+
+    /// Returns the contribution of an Orchard bundle with the given numbers of spends
+    /// and outputs to the ZIP 317 conventional fee of its transaction.
+    ///
+    /// Unlike [`FeeRule::fee_required`], this does not apply the grace actions, which
+    /// are shared with the other components of the transaction.
+    pub fn orchard_fee_contribution(
+        &self,
+        spends: usize,
+        outputs: usize,
+    ) -> Result<NoteValue, OverflowError> {
+        self.fee_for(logical_actions(spends, outputs))
+    }
+
+    fn fee_for(&self, logical_actions: usize) -> Result<NoteValue, OverflowError> {
+        u64::try_from(logical_actions)
+            .ok()
+            .and_then(|actions| actions.checked_mul(self.marginal_fee))
+            .map(NoteValue::from_raw)
+            .ok_or(OverflowError)
+    }
+}
+
+impl FeeRule for Zip317FeeRule {
+    fn fee_required(&self, spends: usize, outputs: usize) -> Result<NoteValue, OverflowError> {
+        self.fee_for(logical_actions(spends, outputs).max(self.grace_actions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{logical_actions, FeeRule, Zip317FeeRule};
+
+    #[test]
+    fn zip317_fees() {
+        let rule = Zip317FeeRule::standard();
+        let fee = |spends, outputs| rule.fee_required(spends, outputs).unwrap().inner();
+
+        // The minimum fee covers the grace actions.
+        assert_eq!(fee(0, 0), 10_000);
+        assert_eq!(fee(1, 0), 10_000);
+        assert_eq!(fee(1, 2), 10_000);
+        // Beyond that, each spend or output that requires another action costs more.
+        assert_eq!(fee(3, 1), 15_000);
+        assert_eq!(fee(2, 5), 25_000);
+
+        // The contribution of the Orchard bundle alone has no grace actions.
+        assert_eq!(logical_actions(0, 0), 2);
+        assert_eq!(logical_actions(1, 0), 2);
+        assert_eq!(logical_actions(4, 3), 4);
+        assert_eq!(rule.orchard_fee_contribution(0, 0).unwrap().inner(), 10_000);
+        assert_eq!(rule.orchard_fee_contribution(3, 1).unwrap().inner(), 15_000);
+
+        let rule = Zip317FeeRule::non_standard(u64::MAX, 0);
+        assert!(rule.fee_required(2, 2).is_err());
+    }
+}
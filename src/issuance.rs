@@ -0,0 +1,464 @@
+//! Issuance of non-native assets.
+//!
+//! An [`IssueBundle`] creates new notes of one or more non-native assets, all issued
+//! under a single [`IssuanceValidatingKey`]. Each [`IssueAction`] issues notes of the
+//! asset identified by its asset description, and may finalize that asset so that no
+//! further issuance of it is possible.
+//!
+//! This is modelled on [ZIP 227: Issuance of Zcash Shielded Assets][zip227], but its
+//! bundle commitment and signatures are not those of ZIP 227, so issue bundles are not
+//! interoperable with OrchardZSA.
+//!
+//! [zip227]: https://zips.z.cash/zip-0227
+
+use core::fmt;
+
+use blake2b_simd::{Hash as Blake2bHash, Params, State};
+use rand::{CryptoRng, RngCore};
+
+use crate::{
+    keys::{IssuanceAuthorizingKey, IssuanceValidatingKey},
+    note::{AssetBase, Note, Nullifier},
+    primitives::redpallas::{self, SpendAuth},
+    value::NoteValue,
+    Address,
+};
+
+const ZCASH_ISSUE_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdSAIssueHash";
+const ZCASH_ISSUE_ACTIONS_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdIssuActHash";
+const ZCASH_ISSUE_NOTES_HASH_PERSONALIZATION: &[u8; 16] = b"ZTxIdIAcNoteHash";
+
+fn hasher(personal: &[u8; 16]) -> State {
+    Params::new().hash_length(32).personal(personal).to_state()
+}
+
+/// Errors that can occur when constructing or validating an [`IssueBundle`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The asset description is empty or too long.
+    InvalidAssetDescription,
+    /// Notes were added to an action that has already been finalized.
+    ActionFinalized,
+    /// More than one action in the bundle issues the same asset.
+    DuplicateAsset,
+    /// The asset of an issued note does not match the asset of its action.
+    NoteAssetMismatch,
+    /// The asset has been finalized by an earlier issuance.
+    AssetFinalized,
+    /// The issuance authorizing key does not correspond to the bundle's issuance
+    /// validating key.
+    IssuanceKeyMismatch,
+    /// The issuance authorization signature is invalid.
+    InvalidSignature,
+    /// The total issued value of an asset overflowed.
+    ValueOverflow,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Error::InvalidAssetDescription => "Asset description is empty or too long",
+            Error::ActionFinalized => "Issue action has already been finalized",
+            Error::DuplicateAsset => "More than one issue action for the same asset",
+            Error::NoteAssetMismatch => "Issued note does not match the asset of its action",
+            Error::AssetFinalized => "Asset has already been finalized",
+            Error::IssuanceKeyMismatch => "Issuance key does not match the bundle",
+            Error::InvalidSignature => "Issuance authorization signature is invalid",
+            Error::ValueOverflow => "Total issued value overflowed",
+        })
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The issuance of notes of a single asset.
+#[derive(Debug, Clone)]
+pub struct IssueAction {
+    asset_desc: String,
+    notes: Vec<Note>,
+    finalize: bool,
+}
+
+impl IssueAction {
+    /// Returns the description of the asset issued by this action.
+    pub fn asset_desc(&self) -> &str {
+        &self.asset_desc
+    }
+
+    /// Returns the notes issued by this action.
+    pub fn notes(&self) -> &[Note] {
+        &self.notes
+    }
+
+    /// Returns whether this action finalizes its asset.
+    pub fn is_finalized(&self) -> bool {
+        self.finalize
+    }
+
+    /// Checks that every note in this action is of the asset described by this action,
+    /// and returns that asset along with the total value issued.
+    pub fn verify_supply(
+        &self,
+        ik: &IssuanceValidatingKey,
+    ) -> Result<(AssetBase, NoteValue), Error> {
+        let asset =
+            AssetBase::derive(ik, &self.asset_desc).ok_or(Error::InvalidAssetDescription)?;
+        let mut total = 0u64;
+        for note in &self.notes {
+            if note.asset() != asset {
+                return Err(Error::NoteAssetMismatch);
+            }
+            total = total
+                .checked_add(note.value().inner())
+                .ok_or(Error::ValueOverflow)?;
+        }
+        Ok((asset, NoteValue::from_raw(total)))
+    }
+}
+
+/// Defines the authorization type of an issue bundle.
+pub trait IssueAuth: fmt::Debug {}
+
+/// Marker for an issue bundle to which notes may still be added.
+#[derive(Debug, Clone)]
+pub struct Unauthorized;
+
+impl IssueAuth for Unauthorized {}
+
+/// Marker for an issue bundle that is ready to be signed.
+#[derive(Debug, Clone)]
+pub struct Prepared {
+    sighash: [u8; 32],
+}
+
+impl IssueAuth for Prepared {}
+
+/// Authorizing data for an issue bundle, ready to be committed to the ledger.
+#[derive(Debug, Clone)]
+pub struct Signed {
+    signature: redpallas::Signature<SpendAuth>,
+}
+
+impl IssueAuth for Signed {}
+
+impl Signed {
+    /// Returns the issuance authorization signature.
+    pub fn signature(&self) -> &redpallas::Signature<SpendAuth> {
+        &self.signature
+    }
+}
+
+/// A bundle of actions that issue notes of Zcash Shielded Assets.
+#[derive(Debug, Clone)]
+pub struct IssueBundle<T: IssueAuth> {
+    ik: IssuanceValidatingKey,
+    actions: Vec<IssueAction>,
+    authorization: T,
+}
+
+impl<T: IssueAuth> IssueBundle<T> {
+    /// Returns the issuance validating key for this bundle.
+    pub fn ik(&self) -> &IssuanceValidatingKey {
+        &self.ik
+    }
+
+    /// Returns the actions of this bundle.
+    pub fn actions(&self) -> &[IssueAction] {
+        &self.actions
+    }
+
+    /// Returns the authorization for this bundle.
+    pub fn authorization(&self) -> &T {
+        &self.authorization
+    }
+
+    /// Computes a commitment to the effects of this bundle, suitable for inclusion within
+    /// a transaction ID.
+    ///
+    /// This follows the structure of the ZIP 227 issuance digest, but length-prefixes
+    /// each asset description, and so does not match it.
+    pub fn commitment(&self) -> IssueBundleCommitment {
+        let mut h = hasher(ZCASH_ISSUE_HASH_PERSONALIZATION);
+        let mut ah = hasher(ZCASH_ISSUE_ACTIONS_HASH_PERSONALIZATION);
+
+        for action in &self.actions {
+            let mut nh = hasher(ZCASH_ISSUE_NOTES_HASH_PERSONALIZATION);
+            for note in &action.notes {
+                nh.update(&note.recipient().to_raw_address_bytes());
+                nh.update(&note.value().to_bytes());
+                nh.update(&note.asset().to_bytes());
+                nh.update(&note.rho().to_bytes());
+                nh.update(note.rseed().as_bytes());
+            }
+            ah.update(nh.finalize().as_bytes());
+            // The description is length-prefixed, so that it cannot absorb the bytes
+            // that follow it.
+            ah.update(&(action.asset_desc.len() as u32).to_le_bytes());
+            ah.update(action.asset_desc.as_bytes());
+            ah.update(&[u8::from(action.finalize)]);
+        }
+
+        h.update(ah.finalize().as_bytes());
+        h.update(&self.ik.to_bytes());
+        IssueBundleCommitment(h.finalize())
+    }
+
+    /// Checks the actions of this bundle, and returns the asset and total value issued by
+    /// each action.
+    ///
+    /// `is_finalized` reports whether an asset has been finalized by an earlier issuance.
+    fn verify_actions(
+        &self,
+        is_finalized: impl Fn(&AssetBase) -> bool,
+    ) -> Result<Vec<(AssetBase, NoteValue)>, Error> {
+        let mut supply: Vec<(AssetBase, NoteValue)> = vec![];
+        for action in &self.actions {
+            let (asset, value) = action.verify_supply(&self.ik)?;
+            if supply.iter().any(|(a, _)| *a == asset) {
+                return Err(Error::DuplicateAsset);
+            }
+            if is_finalized(&asset) {
+                return Err(Error::AssetFinalized);
+            }
+            supply.push((asset, value));
+        }
+        Ok(supply)
+    }
+}
+
+impl IssueBundle<Unauthorized> {
+    /// Constructs a new empty issue bundle for the given issuer.
+    pub fn new(ik: IssuanceValidatingKey) -> Self {
+        IssueBundle {
+            ik,
+            actions: vec![],
+            authorization: Unauthorized,
+        }
+    }
+
+    /// Adds a note of the asset described by `asset_desc` to this bundle, and returns the
+    /// asset.
+    ///
+    /// If `finalize` is set, no further notes of this asset may be added to the bundle,
+    /// and the asset may not be issued in later bundles.
+    pub fn add_recipient(
+        &mut self,
+        asset_desc: String,
+        recipient: Address,
+        value: NoteValue,
+        finalize: bool,
+        mut rng: impl RngCore,
+    ) -> Result<AssetBase, Error> {
+        let asset =
+            AssetBase::derive(&self.ik, &asset_desc).ok_or(Error::InvalidAssetDescription)?;
+
+        // There is no spent note from which to take rho, so we use a random value, as for
+        // dummy notes.
+        let note = Note::new_with_asset(
+            recipient,
+            value,
+            asset,
+            Nullifier::dummy(&mut rng),
+            &mut rng,
+        );
+
+        match self.actions.iter_mut().find(|a| a.asset_desc == asset_desc) {
+            Some(action) if action.finalize => return Err(Error::ActionFinalized),
+            Some(action) => {
+                action.notes.push(note);
+                action.finalize = finalize;
+            }
+            None => self.actions.push(IssueAction {
+                asset_desc,
+                notes: vec![note],
+                finalize,
+            }),
+        }
+
+        Ok(asset)
+    }
+
+    /// Loads the sighash into this bundle, preparing it for signing.
+    pub fn prepare(self, sighash: [u8; 32]) -> IssueBundle<Prepared> {
+        IssueBundle {
+            ik: self.ik,
+            actions: self.actions,
+            authorization: Prepared { sighash },
+        }
+    }
+}
+
+impl IssueBundle<Prepared> {
+    /// Signs this bundle with the given issuance authorizing key.
+    pub fn sign<R: RngCore + CryptoRng>(
+        self,
+        rng: R,
+        isk: &IssuanceAuthorizingKey,
+    ) -> Result<IssueBundle<Signed>, Error> {
+        if IssuanceValidatingKey::from(isk) != self.ik {
+            return Err(Error::IssuanceKeyMismatch);
+        }
+        // Check the actions now, so that we never sign an invalid bundle.
+        self.verify_actions(|_| false)?;
+
+        let signature = isk.sign(rng, &self.authorization.sighash);
+        Ok(IssueBundle {
+            ik: self.ik,
+            actions: self.actions,
+            authorization: Signed { signature },
+        })
+    }
+}
+
+impl IssueBundle<Signed> {
+    /// Validates this bundle against the given sighash.
+    ///
+    /// `is_finalized` reports whether an asset has been finalized by an earlier issuance.
+    /// On success, returns the asset and total value issued by each action.
+    pub fn verify(
+        &self,
+        sighash: &[u8; 32],
+        is_finalized: impl Fn(&AssetBase) -> bool,
+    ) -> Result<Vec<(AssetBase, NoteValue)>, Error> {
+        self.ik
+            .verify(sighash, &self.authorization.signature)
+            .map_err(|_| Error::InvalidSignature)?;
+        self.verify_actions(is_finalized)
+    }
+}
+
+/// A commitment to the effects of an issue bundle.
+#[derive(Debug)]
+pub struct IssueBundleCommitment(pub Blake2bHash);
+
+impl From<IssueBundleCommitment> for [u8; 32] {
+    fn from(commitment: IssueBundleCommitment) -> Self {
+        commitment.0.as_bytes().try_into().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use incrementalmerkletree::{bridgetree::BridgeTree, Tree};
+    use rand::rngs::OsRng;
+
+    use super::{Error, IssueBundle};
+    use crate::{
+        builder::{self, Builder},
+        bundle::Flags,
+        keys::{FullViewingKey, IssuanceAuthorizingKey, IssuanceValidatingKey, Scope, SpendingKey},
+        note::ExtractedNoteCommitment,
+        note_encryption::ZSA_MEMO_SIZE,
+        tree::{MerkleHashOrchard, MerklePath},
+        value::NoteValue,
+    };
+
+    #[test]
+    fn issue_and_transfer() {
+        let mut rng = OsRng;
+        let sk = SpendingKey::from_bytes([0; 32]).unwrap();
+        let isk = IssuanceAuthorizingKey::from_zip32_seed(&[0; 32], 133, 0).unwrap();
+        let ik = IssuanceValidatingKey::from(&isk);
+        let fvk = FullViewingKey::from(&sk);
+        let recipient = fvk.address_at(0u32, Scope::External);
+
+        // Issue some notes.
+        let mut bundle = IssueBundle::new(ik);
+        let asset = bundle
+            .add_recipient(
+                "Asset".into(),
+                recipient,
+                NoteValue::from_raw(10),
+                false,
+                rng,
+            )
+            .unwrap();
+        bundle
+            .add_recipient("Asset".into(), recipient, NoteValue::from_raw(5), true, rng)
+            .unwrap();
+        assert_eq!(
+            bundle.add_recipient(
+                "Asset".into(),
+                recipient,
+                NoteValue::from_raw(1),
+                false,
+                rng
+            ),
+            Err(Error::ActionFinalized)
+        );
+
+        let sighash = bundle.commitment().into();
+        let signed = bundle.prepare(sighash).sign(rng, &isk).unwrap();
+        let supply = signed.verify(&sighash, |_| false).unwrap();
+        assert_eq!(supply.len(), 1);
+        assert_eq!(supply[0].0, asset);
+        assert_eq!(supply[0].1.inner(), 15);
+        assert_eq!(
+            signed.verify(&sighash, |a| *a == asset),
+            Err(Error::AssetFinalized)
+        );
+        assert_eq!(
+            signed.verify(&[0; 32], |_| false),
+            Err(Error::InvalidSignature)
+        );
+
+        // Transfer one of the issued notes.
+        let note = signed.actions()[0].notes()[0];
+        let cmx: ExtractedNoteCommitment = note.commitment().into();
+        let mut tree = BridgeTree::<MerkleHashOrchard, 32>::new(0);
+        tree.append(&MerkleHashOrchard::from_cmx(&cmx));
+        let position = tree.witness().unwrap();
+        let root = tree.root(0).unwrap();
+        let merkle_path: MerklePath =
+            (position, tree.authentication_path(position, &root).unwrap()).into();
+        let anchor = root.into();
+
+        // The spends and outputs of a non-native asset must balance.
+        let mut builder = Builder::new(Flags::from_parts(true, true), anchor);
+        builder.add_spend(fvk.clone(), note, merkle_path).unwrap();
+        builder
+            .add_recipient_with_asset(None, recipient, NoteValue::from_raw(9), asset, None)
+            .unwrap();
+        assert!(matches!(
+            builder.build::<i64>(rng),
+            Err(builder::Error::UnbalancedAsset(a)) if a == asset
+        ));
+
+        let merkle_path: MerklePath =
+            (position, tree.authentication_path(position, &root).unwrap()).into();
+        let mut builder = Builder::new(Flags::from_parts(true, true), anchor);
+        builder.add_spend(fvk.clone(), note, merkle_path).unwrap();
+
+        // Memos of non-native notes cannot use their last 32 bytes.
+        let mut memo = [0xf6; 512];
+        assert!(builder
+            .add_recipient_with_asset(None, recipient, NoteValue::from_raw(4), asset, Some(memo))
+            .is_err());
+        memo[ZSA_MEMO_SIZE..].fill(0);
+        builder
+            .add_recipient_with_asset(None, recipient, NoteValue::from_raw(4), asset, Some(memo))
+            .unwrap();
+        builder
+            .add_recipient_with_asset(None, recipient, NoteValue::from_raw(6), asset, None)
+            .unwrap();
+        let transfer = builder.build::<i64>(rng).unwrap();
+        assert_eq!(*transfer.value_balance(), 0);
+
+        // The recipient can decrypt the transferred notes, including their asset.
+        let received = transfer.decrypt_outputs_with_keys(&[fvk.to_ivk(Scope::External)]);
+        assert_eq!(received.len(), 2);
+        assert!(received
+            .iter()
+            .all(|(_, _, note, _, _)| note.asset() == asset));
+        assert_eq!(
+            received
+                .iter()
+                .map(|(_, _, note, _, _)| note.value().inner())
+                .sum::<u64>(),
+            10
+        );
+        assert!(received
+            .iter()
+            .any(|(_, _, note, _, m)| note.value().inner() == 4 && *m == memo));
+    }
+}
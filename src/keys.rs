@@ -1,5 +1,7 @@
 //! Key structures for Orchard.
 
+use core::cmp::Ordering;
+use core::fmt;
 use core::mem;
 use std::io::{self, Read, Write};
 
@@ -12,31 +14,54 @@ use group::{
     Curve, GroupEncoding,
 };
 use pasta_curves::pallas;
+#[cfg(feature = "zsa")]
+use rand::CryptoRng;
 use rand::RngCore;
 use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
 use zcash_note_encryption::EphemeralKeyBytes;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use crate::{
     address::Address,
     primitives::redpallas::{self, SpendAuth},
     spec::{
         commit_ivk, diversify_hash, extract_p, ka_orchard, prf_nf, to_base, to_scalar,
-        NonIdentityPallasPoint, NonZeroPallasBase, NonZeroPallasScalar, PrfExpand,
+        NonIdentityPallasPoint, NonZeroPallasBase, NonZeroPallasScalar, PrfExpand, SecretField,
     },
     zip32::{self, ChildIndex, ExtendedSpendingKey},
 };
 
+pub mod addresses;
+
 const KDF_ORCHARD_PERSONALIZATION: &[u8; 16] = b"Zcash_OrchardKDF";
 const ZIP32_PURPOSE: u32 = 32;
+#[cfg(feature = "zsa")]
+const ZIP32_PURPOSE_FOR_ISSUANCE: u32 = 227;
 
 /// A spending key, from which all key material is derived.
 ///
 /// $\mathsf{sk}$ as defined in [Zcash Protocol Spec § 4.2.3: Orchard Key Components][orchardkeycomponents].
 ///
 /// [orchardkeycomponents]: https://zips.z.cash/protocol/nu5.pdf#orchardkeycomponents
-#[derive(Debug, Copy, Clone)]
+///
+/// The key is zeroized when it is dropped.
+#[derive(Debug, Clone)]
 pub struct SpendingKey([u8; 32]);
 
+impl Zeroize for SpendingKey {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for SpendingKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SpendingKey {}
+
 impl ConstantTimeEq for SpendingKey {
     fn ct_eq(&self, other: &Self) -> Choice {
         self.to_bytes().ct_eq(other.to_bytes())
@@ -100,6 +125,30 @@ impl SpendingKey {
         ];
         ExtendedSpendingKey::from_path(seed, path).map(|esk| esk.sk())
     }
+
+    /// Derives the Orchard spending key for the given coin type and account from a
+    /// [BIP 39] mnemonic phrase and an optional passphrase.
+    ///
+    /// The phrase must use the English wordlist and have a valid checksum. The phrase
+    /// and passphrase are normalized to Unicode NFKD before the seed is derived, as
+    /// BIP 39 requires, so they may be given in any normalization form.
+    ///
+    /// [BIP 39]: https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki
+    #[cfg(feature = "bip39")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bip39")))]
+    pub fn from_mnemonic(
+        phrase: &str,
+        passphrase: Option<&str>,
+        coin_type: u32,
+        account: u32,
+    ) -> Result<Self, zip32::MnemonicError> {
+        let mnemonic = bip39::Mnemonic::parse_in(bip39::Language::English, phrase)
+            .map_err(zip32::MnemonicError::InvalidMnemonic)?;
+        let mut seed = mnemonic.to_seed(passphrase.unwrap_or(""));
+        let sk = Self::from_zip32_seed(&seed, coin_type, account);
+        seed.zeroize();
+        Ok(sk?)
+    }
 }
 
 /// A spend authorizing key, used to create spend authorization signatures.
@@ -108,9 +157,13 @@ impl SpendingKey {
 /// $\mathsf{ask}$ as defined in [Zcash Protocol Spec § 4.2.3: Orchard Key Components][orchardkeycomponents].
 ///
 /// [orchardkeycomponents]: https://zips.z.cash/protocol/nu5.pdf#orchardkeycomponents
+///
+/// The key is zeroized when it is dropped.
 #[derive(Clone, Debug)]
 pub struct SpendAuthorizingKey(redpallas::SigningKey<SpendAuth>);
 
+impl ZeroizeOnDrop for SpendAuthorizingKey {}
+
 impl SpendAuthorizingKey {
     /// Derives ask from sk. Internal use only, does not enforce all constraints.
     fn derive_inner(sk: &SpendingKey) -> pallas::Scalar {
@@ -203,6 +256,95 @@ impl SpendValidatingKey {
     }
 }
 
+/// An issuance authorizing key, used to sign bundles that issue non-native assets.
+///
+/// Issuance keys are derived from a seed on their own ZIP 32 path, separately from
+/// spending keys, as [ZIP 227][issuancekeys] specifies. They are RedPallas keys, and are
+/// not yet interoperable with ZIP 227 implementations.
+///
+/// [issuancekeys]: https://zips.z.cash/zip-0227#issuance-key-derivation
+#[cfg(feature = "zsa")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zsa")))]
+#[derive(Clone, Debug)]
+pub struct IssuanceAuthorizingKey(redpallas::SigningKey<SpendAuth>);
+
+#[cfg(feature = "zsa")]
+impl IssuanceAuthorizingKey {
+    /// Derives the issuance authorizing key for the given seed, coin type, and account.
+    ///
+    /// The key is derived from the extended key at the path
+    /// `m_Issuance / 227' / coin_type' / account'`.
+    pub fn from_zip32_seed(
+        seed: &[u8],
+        coin_type: u32,
+        account: u32,
+    ) -> Result<Self, zip32::Error> {
+        let path = &[
+            ChildIndex::try_from(ZIP32_PURPOSE_FOR_ISSUANCE)?,
+            ChildIndex::try_from(coin_type)?,
+            ChildIndex::try_from(account)?,
+        ];
+        let sk = ExtendedSpendingKey::issuance_from_path(seed, path)?.sk();
+        let isk = to_scalar(PrfExpand::ZsaIsk.expand(sk.to_bytes()));
+        // isk = 0 occurs with negligible probability.
+        if bool::from(isk.is_zero()) {
+            return Err(zip32::Error::InvalidSpendingKey);
+        }
+        Ok(IssuanceAuthorizingKey(isk.to_repr().try_into().unwrap()))
+    }
+
+    /// Signs `msg` with this issuance authorizing key.
+    pub fn sign<R: RngCore + CryptoRng>(
+        &self,
+        rng: R,
+        msg: &[u8],
+    ) -> redpallas::Signature<SpendAuth> {
+        self.0.sign(rng, msg)
+    }
+}
+
+/// A key used to validate the signatures of bundles that issue non-native assets.
+///
+/// Together with an asset description, this identifies a non-native asset; see
+/// [`AssetBase::derive`].
+///
+/// [`AssetBase::derive`]: crate::note::AssetBase::derive
+#[cfg(feature = "zsa")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zsa")))]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct IssuanceValidatingKey(redpallas::VerificationKey<SpendAuth>);
+
+#[cfg(feature = "zsa")]
+impl From<&IssuanceAuthorizingKey> for IssuanceValidatingKey {
+    fn from(isk: &IssuanceAuthorizingKey) -> Self {
+        IssuanceValidatingKey((&isk.0).into())
+    }
+}
+
+#[cfg(feature = "zsa")]
+impl IssuanceValidatingKey {
+    /// Converts this issuance validating key to its serialized form.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        <[u8; 32]>::from(&self.0)
+    }
+
+    /// Parses an issuance validating key from its serialized form.
+    pub fn from_bytes(bytes: [u8; 32]) -> Option<Self> {
+        <redpallas::VerificationKey<SpendAuth>>::try_from(bytes)
+            .ok()
+            .map(IssuanceValidatingKey)
+    }
+
+    /// Verifies a purported `signature` over `msg` made by this issuance validating key.
+    pub fn verify(
+        &self,
+        msg: &[u8],
+        signature: &redpallas::Signature<SpendAuth>,
+    ) -> Result<(), reddsa::Error> {
+        self.0.verify(msg, signature)
+    }
+}
+
 /// A key used to derive [`Nullifier`]s from [`Note`]s.
 ///
 /// $\mathsf{nk}$ as defined in [Zcash Protocol Spec § 4.2.3: Orchard Key Components][orchardkeycomponents].
@@ -210,34 +352,51 @@ impl SpendValidatingKey {
 /// [`Nullifier`]: crate::note::Nullifier
 /// [`Note`]: crate::note::Note
 /// [orchardkeycomponents]: https://zips.z.cash/protocol/nu5.pdf#orchardkeycomponents
-#[derive(Copy, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub(crate) struct NullifierDerivingKey(pallas::Base);
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct NullifierDerivingKey(SecretField<pallas::Base>);
+
+impl Zeroize for NullifierDerivingKey {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for NullifierDerivingKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for NullifierDerivingKey {}
 
 impl NullifierDerivingKey {
     pub(crate) fn inner(&self) -> pallas::Base {
-        self.0
+        *self.0
     }
 }
 
 impl From<&SpendingKey> for NullifierDerivingKey {
     fn from(sk: &SpendingKey) -> Self {
-        NullifierDerivingKey(to_base(PrfExpand::OrchardNk.expand(&sk.0)))
+        NullifierDerivingKey(SecretField::new(to_base(
+            PrfExpand::OrchardNk.expand(&sk.0),
+        )))
     }
 }
 
 impl NullifierDerivingKey {
     pub(crate) fn prf_nf(&self, rho: pallas::Base) -> pallas::Base {
-        prf_nf(self.0, rho)
+        prf_nf(*self.0, rho)
     }
 
     /// Converts this nullifier deriving key to its serialized form.
-    pub(crate) fn to_bytes(self) -> [u8; 32] {
-        <[u8; 32]>::from(self.0)
+    pub(crate) fn to_bytes(&self) -> [u8; 32] {
+        <[u8; 32]>::from(*self.0)
     }
 
     pub(crate) fn from_bytes(bytes: &[u8]) -> Option<Self> {
         let nk_bytes = <[u8; 32]>::try_from(bytes).ok()?;
-        let nk = pallas::Base::from_repr(nk_bytes).map(NullifierDerivingKey);
+        let nk =
+            pallas::Base::from_repr(nk_bytes).map(|nk| NullifierDerivingKey(SecretField::new(nk)));
         if nk.is_some().into() {
             Some(nk.unwrap())
         } else {
@@ -251,28 +410,45 @@ impl NullifierDerivingKey {
 /// $\mashsf{rivk}$ as defined in [Zcash Protocol Spec § 4.2.3: Orchard Key Components][orchardkeycomponents].
 ///
 /// [orchardkeycomponents]: https://zips.z.cash/protocol/nu5.pdf#orchardkeycomponents
-#[derive(Copy, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub(crate) struct CommitIvkRandomness(pallas::Scalar);
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct CommitIvkRandomness(SecretField<pallas::Scalar>);
+
+impl Zeroize for CommitIvkRandomness {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for CommitIvkRandomness {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for CommitIvkRandomness {}
 
 impl From<&SpendingKey> for CommitIvkRandomness {
     fn from(sk: &SpendingKey) -> Self {
-        CommitIvkRandomness(to_scalar(PrfExpand::OrchardRivk.expand(&sk.0)))
+        CommitIvkRandomness(SecretField::new(to_scalar(
+            PrfExpand::OrchardRivk.expand(&sk.0),
+        )))
     }
 }
 
 impl CommitIvkRandomness {
     pub(crate) fn inner(&self) -> pallas::Scalar {
-        self.0
+        *self.0
     }
 
     /// Converts this nullifier deriving key to its serialized form.
-    pub(crate) fn to_bytes(self) -> [u8; 32] {
-        <[u8; 32]>::from(self.0)
+    pub(crate) fn to_bytes(&self) -> [u8; 32] {
+        <[u8; 32]>::from(*self.0)
     }
 
     pub(crate) fn from_bytes(bytes: &[u8]) -> Option<Self> {
         let rivk_bytes = <[u8; 32]>::try_from(bytes).ok()?;
-        let rivk = pallas::Scalar::from_repr(rivk_bytes).map(CommitIvkRandomness);
+        let rivk = pallas::Scalar::from_repr(rivk_bytes)
+            .map(|rivk| CommitIvkRandomness(SecretField::new(rivk)));
         if rivk.is_some().into() {
             Some(rivk.unwrap())
         } else {
@@ -344,14 +520,14 @@ impl FullViewingKey {
     /// Returns either `rivk` or `rivk_internal` based on `scope`.
     pub(crate) fn rivk(&self, scope: Scope) -> CommitIvkRandomness {
         match scope {
-            Scope::External => self.rivk,
+            Scope::External => self.rivk.clone(),
             Scope::Internal => {
                 let k = self.rivk.0.to_repr();
                 let ak = self.ak.to_bytes();
                 let nk = self.nk.to_bytes();
-                CommitIvkRandomness(to_scalar(
+                CommitIvkRandomness(SecretField::new(to_scalar(
                     PrfExpand::OrchardRivkInternal.with_ad_slices(&k, &[&ak, &nk]),
-                ))
+                )))
             }
         }
     }
@@ -450,7 +626,7 @@ impl FullViewingKey {
     fn derive_internal(&self) -> Self {
         FullViewingKey {
             ak: self.ak.clone(),
-            nk: self.nk,
+            nk: self.nk.clone(),
             rivk: self.rivk(Scope::Internal),
         }
     }
@@ -505,13 +681,53 @@ impl From<[u8; 11]> for DiversifierIndex {
     }
 }
 
+/// Diversifier indices are ordered as 88-bit unsigned integers.
+impl Ord for DiversifierIndex {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for DiversifierIndex {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl DiversifierIndex {
     /// Returns the raw bytes of the diversifier index.
     pub fn to_bytes(&self) -> &[u8; 11] {
         &self.0
     }
+
+    /// Increments this diversifier index.
+    ///
+    /// Returns an error, and leaves the index unchanged, if it is already the largest
+    /// index, $2^{88} - 1$.
+    pub fn increment(&mut self) -> Result<(), DiversifierIndexOverflowError> {
+        match self.0.iter().position(|&b| b != 0xff) {
+            Some(k) => {
+                self.0[k] += 1;
+                self.0[..k].fill(0);
+                Ok(())
+            }
+            None => Err(DiversifierIndexOverflowError),
+        }
+    }
 }
 
+/// The error returned when incrementing the largest [`DiversifierIndex`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DiversifierIndexOverflowError;
+
+impl fmt::Display for DiversifierIndexOverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Orchard diversifier index overflow")
+    }
+}
+
+impl std::error::Error for DiversifierIndexOverflowError {}
+
 impl DiversifierKey {
     /// Returns the diversifier at the given index.
     pub fn get(&self, j: impl Into<DiversifierIndex>) -> Diversifier {
@@ -790,9 +1006,25 @@ impl ConditionallySelectable for DiversifiedTransmissionKey {
 /// Defined in [section 5.4.5.5: Orchard Key Agreement][concreteorchardkeyagreement].
 ///
 /// [concreteorchardkeyagreement]: https://zips.z.cash/protocol/nu5.pdf#concreteorchardkeyagreement
+///
+/// The key is zeroized when it is dropped.
 #[derive(Debug)]
 pub struct EphemeralSecretKey(pub(crate) NonZeroPallasScalar);
 
+impl Zeroize for EphemeralSecretKey {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for EphemeralSecretKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for EphemeralSecretKey {}
+
 impl ConstantTimeEq for EphemeralSecretKey {
     fn ct_eq(&self, other: &Self) -> subtle::Choice {
         self.0.ct_eq(&other.0)
@@ -845,9 +1077,25 @@ impl EphemeralPublicKey {
 /// Defined in [section 5.4.5.5: Orchard Key Agreement][concreteorchardkeyagreement].
 ///
 /// [concreteorchardkeyagreement]: https://zips.z.cash/protocol/nu5.pdf#concreteorchardkeyagreement
+///
+/// The secret is zeroized when it is dropped.
 #[derive(Debug)]
 pub struct SharedSecret(NonIdentityPallasPoint);
 
+impl Zeroize for SharedSecret {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for SharedSecret {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SharedSecret {}
+
 impl SharedSecret {
     /// For checking test vectors only.
     #[cfg(test)]
@@ -957,17 +1205,102 @@ pub mod testing {
 mod tests {
     use ff::PrimeField;
     use proptest::prelude::*;
+    use rand::rngs::OsRng;
 
     use super::{
         testing::{arb_diversifier_index, arb_diversifier_key, arb_esk, arb_spending_key},
         *,
     };
     use crate::{
-        note::{ExtractedNoteCommitment, Nullifier, RandomSeed},
-        value::NoteValue,
+        note::{commitment::NoteCommitTrapdoor, ExtractedNoteCommitment, Nullifier, RandomSeed},
+        value::{NoteValue, ValueCommitTrapdoor},
         Note,
     };
 
+    #[cfg(feature = "bip39")]
+    #[test]
+    fn from_mnemonic() {
+        use crate::zip32::MnemonicError;
+
+        // The all-zero entropy vector from BIP 39, whose seed with the passphrase
+        // "TREZOR" is given there.
+        let phrase = "abandon abandon abandon abandon abandon abandon \
+                      abandon abandon abandon abandon abandon about";
+        let seed = hex::decode(
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+        )
+        .unwrap();
+        assert_eq!(
+            SpendingKey::from_mnemonic(phrase, Some("TREZOR"), 133, 0)
+                .unwrap()
+                .to_bytes(),
+            SpendingKey::from_zip32_seed(&seed, 133, 0)
+                .unwrap()
+                .to_bytes(),
+        );
+
+        // An absent passphrase is the empty passphrase.
+        assert_eq!(
+            SpendingKey::from_mnemonic(phrase, None, 133, 0)
+                .unwrap()
+                .to_bytes(),
+            SpendingKey::from_mnemonic(phrase, Some(""), 133, 0)
+                .unwrap()
+                .to_bytes(),
+        );
+
+        // Composed and decomposed forms of a passphrase give the same key.
+        assert_eq!(
+            SpendingKey::from_mnemonic(phrase, Some("caf\u{e9}"), 133, 0)
+                .unwrap()
+                .to_bytes(),
+            SpendingKey::from_mnemonic(phrase, Some("cafe\u{301}"), 133, 0)
+                .unwrap()
+                .to_bytes(),
+        );
+
+        let bad_checksum = "abandon abandon abandon abandon abandon abandon \
+                            abandon abandon abandon abandon abandon abandon";
+        assert_eq!(
+            SpendingKey::from_mnemonic(bad_checksum, None, 133, 0).unwrap_err(),
+            MnemonicError::InvalidMnemonic(bip39::Error::InvalidChecksum),
+        );
+        let unknown_word = "abandon abandon abandon abandon abandon abandon \
+                            abandon abandon abandon abandon abandon zcash";
+        assert_eq!(
+            SpendingKey::from_mnemonic(unknown_word, None, 133, 0).unwrap_err(),
+            MnemonicError::InvalidMnemonic(bip39::Error::UnknownWord(11)),
+        );
+        assert_eq!(
+            SpendingKey::from_mnemonic(phrase, None, 133, 1 << 31).unwrap_err(),
+            MnemonicError::Derivation(zip32::Error::InvalidChildIndex(32)),
+        );
+    }
+
+    #[cfg(feature = "zsa")]
+    #[test]
+    fn issuance_key_derivation() {
+        let seed = [0; 32];
+        let ik = |coin_type, account| {
+            IssuanceValidatingKey::from(
+                &IssuanceAuthorizingKey::from_zip32_seed(&seed, coin_type, account).unwrap(),
+            )
+        };
+        assert_eq!(ik(133, 0), ik(133, 0));
+        assert_ne!(ik(133, 0), ik(133, 1));
+        assert_ne!(ik(133, 0), ik(1, 0));
+
+        // The issuance key is not derived from the spending key of the same account.
+        let sk = SpendingKey::from_zip32_seed(&seed, 133, 0).unwrap();
+        let ak = SpendValidatingKey::from(&SpendAuthorizingKey::from(&sk));
+        assert_ne!(ik(133, 0).to_bytes(), ak.to_bytes());
+
+        assert_eq!(
+            IssuanceAuthorizingKey::from_zip32_seed(&seed, 133, 1 << 31).unwrap_err(),
+            zip32::Error::InvalidChildIndex(32),
+        );
+    }
+
     #[test]
     fn spend_validating_key_from_bytes() {
         // ak_P must not be the identity.
@@ -984,6 +1317,58 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn secrets_are_zeroized() {
+        let mut sk = SpendingKey::from_bytes([7; 32]).unwrap();
+        let fvk = FullViewingKey::from(&sk);
+
+        let mut ask = SpendAuthorizingKey::from(&sk);
+        ask.0.zeroize();
+        assert_eq!(<[u8; 32]>::from(&ask.0), [0; 32]);
+
+        let mut nk = fvk.nk().clone();
+        nk.zeroize();
+        assert_eq!(nk.to_bytes(), [0; 32]);
+
+        let mut rivk = fvk.rivk(Scope::Internal);
+        rivk.zeroize();
+        assert_eq!(rivk.to_bytes(), [0; 32]);
+
+        sk.zeroize();
+        assert_eq!(sk.to_bytes(), &[0; 32]);
+
+        // The non-zero types are reset to their (public) default values.
+        let mut esk = EphemeralSecretKey::from_bytes(&[1; 32]).unwrap();
+        let mut shared_secret = esk.agree(fvk.address_at(0u32, Scope::External).pk_d());
+        esk.zeroize();
+        assert_eq!(*esk.0, pallas::Scalar::one());
+        shared_secret.zeroize();
+        assert_eq!(
+            shared_secret.to_bytes(),
+            NonIdentityPallasPoint::default().to_bytes()
+        );
+
+        let (_, _, note) = Note::dummy(&mut OsRng, None);
+        let mut rcm = note.rseed().rcm(&note.rho());
+        rcm.zeroize();
+        assert_eq!(rcm.to_bytes(), [0; 32]);
+    }
+
+    #[test]
+    fn secrets_are_zeroized_on_drop() {
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<SpendingKey>();
+        assert_zeroize_on_drop::<SpendAuthorizingKey>();
+        assert_zeroize_on_drop::<NullifierDerivingKey>();
+        assert_zeroize_on_drop::<CommitIvkRandomness>();
+        assert_zeroize_on_drop::<EphemeralSecretKey>();
+        assert_zeroize_on_drop::<SharedSecret>();
+        assert_zeroize_on_drop::<redpallas::SigningKey<SpendAuth>>();
+        assert_zeroize_on_drop::<redpallas::SigningKey<redpallas::Binding>>();
+        assert_zeroize_on_drop::<ValueCommitTrapdoor>();
+        assert_zeroize_on_drop::<NoteCommitTrapdoor>();
+    }
+
     proptest! {
         #[test]
         fn key_agreement(
@@ -1013,6 +1398,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn diversifier_index_increment() {
+        let mut j = DiversifierIndex::from(0xffu32);
+        j.increment().unwrap();
+        assert_eq!(j, DiversifierIndex::from(0x100u32));
+
+        let mut j = DiversifierIndex::from(u64::MAX);
+        j.increment().unwrap();
+        let mut expected = [0; 11];
+        expected[8] = 1;
+        assert_eq!(j, DiversifierIndex::from(expected));
+
+        let mut j = DiversifierIndex::from([0xff; 11]);
+        assert_eq!(j.increment(), Err(DiversifierIndexOverflowError));
+        assert_eq!(j, DiversifierIndex::from([0xff; 11]));
+    }
+
+    proptest! {
+        #[test]
+        fn diversifier_index_order(a in any::<u64>(), b in any::<u64>()) {
+            let (j_a, j_b) = (DiversifierIndex::from(a), DiversifierIndex::from(b));
+            assert_eq!(j_a.cmp(&j_b), a.cmp(&b));
+
+            let mut j_next = j_a;
+            j_next.increment().unwrap();
+            assert!(j_a < j_next);
+        }
+    }
+
     #[test]
     fn test_vectors() {
         for tv in crate::test_vectors::keys::test_vectors() {
@@ -1049,7 +1463,8 @@ mod tests {
                 NoteValue::from_raw(tv.note_v),
                 rho,
                 RandomSeed::from_bytes(tv.note_rseed, &rho).unwrap(),
-            );
+            )
+            .unwrap();
 
             let cmx: ExtractedNoteCommitment = note.commitment().into();
             assert_eq!(cmx.to_bytes(), tv.note_cmx);
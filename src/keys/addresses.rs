@@ -0,0 +1,290 @@
+//! Issuance of diversified addresses.
+//!
+//! Every diversifier index gives a valid Orchard address, so a wallet can issue a fresh
+//! address for each payer by stepping through the 88-bit index space. An
+//! [`AddressGenerator`] does this for one [`IncomingViewingKey`], remembering which
+//! indices it has issued and which of those the wallet's scanner has seen receive
+//! funds. It can refuse to issue more than a gap limit of unfunded addresses in a row,
+//! so that a wallet restored from the same seed knows how far beyond the last funded
+//! address it must look.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::{DiversifierIndex, DiversifierIndexOverflowError, IncomingViewingKey};
+use crate::Address;
+
+/// An iterator over the addresses of an [`IncomingViewingKey`], in order of their
+/// diversifier indices.
+///
+/// The iterator ends after the address at the largest diversifier index.
+#[derive(Clone, Debug)]
+pub struct DiversifiedAddresses {
+    ivk: IncomingViewingKey,
+    next: Option<DiversifierIndex>,
+}
+
+impl DiversifiedAddresses {
+    /// Returns an iterator over the addresses of `ivk`, starting at diversifier index
+    /// `start`.
+    pub fn new(ivk: IncomingViewingKey, start: impl Into<DiversifierIndex>) -> Self {
+        DiversifiedAddresses {
+            ivk,
+            next: Some(start.into()),
+        }
+    }
+
+    /// Returns the diversifier index of the next address, or `None` if the iterator
+    /// has ended.
+    pub fn next_index(&self) -> Option<DiversifierIndex> {
+        self.next
+    }
+
+    /// Moves the iterator past the given diversifier index, if it is not already.
+    fn skip_past(&mut self, j: DiversifierIndex) {
+        if matches!(self.next, Some(next) if next <= j) {
+            let mut next = j;
+            self.next = next.increment().ok().map(|()| next);
+        }
+    }
+}
+
+impl Iterator for DiversifiedAddresses {
+    type Item = (DiversifierIndex, Address);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let j = self.next?;
+        self.skip_past(j);
+        Some((j, self.ivk.address_at(j)))
+    }
+}
+
+/// An error that can occur while issuing an address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Every diversifier index has been issued.
+    IndexOverflow,
+    /// The given number of addresses have been issued since the last funded address,
+    /// which is the generator's gap limit.
+    GapLimit(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::IndexOverflow => write!(f, "{}", DiversifierIndexOverflowError),
+            Error::GapLimit(limit) => write!(
+                f,
+                "{} addresses have been issued since the last funded address",
+                limit
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<DiversifierIndexOverflowError> for Error {
+    fn from(_: DiversifierIndexOverflowError) -> Self {
+        Error::IndexOverflow
+    }
+}
+
+/// Issues the addresses of an [`IncomingViewingKey`], and tracks which of them have
+/// received funds.
+#[derive(Clone, Debug)]
+pub struct AddressGenerator {
+    addresses: DiversifiedAddresses,
+    /// The issued diversifier indices, each with whether it has received funds.
+    issued: BTreeMap<DiversifierIndex, bool>,
+    gap_limit: Option<usize>,
+}
+
+impl AddressGenerator {
+    /// Constructs a generator that issues the addresses of `ivk`, starting at
+    /// diversifier index 0, with no gap limit.
+    pub fn new(ivk: IncomingViewingKey) -> Self {
+        AddressGenerator {
+            addresses: DiversifiedAddresses::new(ivk, 0u32),
+            issued: BTreeMap::new(),
+            gap_limit: None,
+        }
+    }
+
+    /// Limits the number of addresses that can be issued after the last funded
+    /// address, or after the start if no address has been funded.
+    pub fn with_gap_limit(mut self, gap_limit: usize) -> Self {
+        self.gap_limit = Some(gap_limit);
+        self
+    }
+
+    /// Returns the diversifier index of the next address to be issued, or `None` if
+    /// every index has been issued.
+    pub fn next_index(&self) -> Option<DiversifierIndex> {
+        self.addresses.next_index()
+    }
+
+    /// Issues the next address, and returns it along with its diversifier index.
+    pub fn issue(&mut self) -> Result<(DiversifierIndex, Address), Error> {
+        if let Some(limit) = self.gap_limit {
+            if self.unfunded_gap() >= limit {
+                return Err(Error::GapLimit(limit));
+            }
+        }
+
+        let (j, address) = self.addresses.next().ok_or(Error::IndexOverflow)?;
+        self.issued.insert(j, false);
+        Ok((j, address))
+    }
+
+    /// Records that `address` has received funds, and returns its diversifier index.
+    ///
+    /// Returns `None` if `address` was not derived from this generator's key. An
+    /// address that this generator did not issue, such as one issued before the wallet
+    /// was restored, is recorded as issued, and no address at or below its index will
+    /// be issued afterwards.
+    pub fn mark_funded(&mut self, address: &Address) -> Option<DiversifierIndex> {
+        let j = self.addresses.ivk.diversifier_index(address)?;
+        self.issued.insert(j, true);
+        self.addresses.skip_past(j);
+        Some(j)
+    }
+
+    /// Returns whether the address at diversifier index `j` has been issued.
+    pub fn is_issued(&self, j: DiversifierIndex) -> bool {
+        self.issued.contains_key(&j)
+    }
+
+    /// Returns whether the address at diversifier index `j` has received funds.
+    pub fn is_funded(&self, j: DiversifierIndex) -> bool {
+        self.issued.get(&j).copied().unwrap_or(false)
+    }
+
+    /// Returns the issued diversifier indices in increasing order, each with whether
+    /// its address has received funds.
+    pub fn issued(&self) -> impl Iterator<Item = (DiversifierIndex, bool)> + '_ {
+        self.issued.iter().map(|(j, funded)| (*j, *funded))
+    }
+
+    /// Returns the largest diversifier index whose address has received funds.
+    pub fn last_funded(&self) -> Option<DiversifierIndex> {
+        self.issued
+            .iter()
+            .rev()
+            .find(|(_, funded)| **funded)
+            .map(|(j, _)| *j)
+    }
+
+    /// Returns the number of addresses that have been issued after the last funded
+    /// address, or after the start if no address has been funded.
+    pub fn unfunded_gap(&self) -> usize {
+        self.issued
+            .values()
+            .rev()
+            .take_while(|funded| !**funded)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AddressGenerator, DiversifiedAddresses, Error};
+    use crate::keys::{DiversifierIndex, FullViewingKey, Scope, SpendingKey};
+
+    fn generator() -> (FullViewingKey, AddressGenerator) {
+        let fvk = FullViewingKey::from(&SpendingKey::from_bytes([7; 32]).unwrap());
+        let generator = AddressGenerator::new(fvk.to_ivk(Scope::External));
+        (fvk, generator)
+    }
+
+    #[test]
+    fn iterates_in_index_order() {
+        let (fvk, _) = generator();
+        let addresses: Vec<_> = DiversifiedAddresses::new(fvk.to_ivk(Scope::Internal), 254u32)
+            .take(3)
+            .collect();
+        for (k, (j, address)) in (254u32..).zip(addresses) {
+            assert_eq!(j, DiversifierIndex::from(k));
+            assert_eq!(address, fvk.address_at(k, Scope::Internal));
+        }
+    }
+
+    #[test]
+    fn ends_at_the_largest_index() {
+        let (fvk, _) = generator();
+        let mut addresses = DiversifiedAddresses::new(fvk.to_ivk(Scope::External), [0xff; 11]);
+        assert_eq!(
+            addresses.next().map(|(j, _)| j),
+            Some(DiversifierIndex::from([0xff; 11]))
+        );
+        assert_eq!(addresses.next_index(), None);
+        assert!(addresses.next().is_none());
+    }
+
+    #[test]
+    fn issues_fresh_addresses() {
+        let (fvk, mut generator) = generator();
+        for k in 0u32..3 {
+            let (j, address) = generator.issue().unwrap();
+            assert_eq!(j, DiversifierIndex::from(k));
+            assert_eq!(address, fvk.address_at(k, Scope::External));
+        }
+        assert_eq!(generator.next_index(), Some(DiversifierIndex::from(3u32)));
+        assert!(generator.is_issued(DiversifierIndex::from(2u32)));
+        assert!(!generator.is_issued(DiversifierIndex::from(3u32)));
+        assert_eq!(generator.unfunded_gap(), 3);
+        assert_eq!(generator.last_funded(), None);
+    }
+
+    #[test]
+    fn tracks_funded_addresses() {
+        let (fvk, mut generator) = generator();
+        let issued: Vec<_> = (0..4).map(|_| generator.issue().unwrap()).collect();
+
+        assert_eq!(generator.mark_funded(&issued[1].1), Some(issued[1].0));
+        assert!(generator.is_funded(issued[1].0));
+        assert!(!generator.is_funded(issued[0].0));
+        assert_eq!(generator.last_funded(), Some(issued[1].0));
+        assert_eq!(generator.unfunded_gap(), 2);
+        assert_eq!(generator.issued().filter(|(_, funded)| *funded).count(), 1);
+
+        // Addresses of other keys, including the internal scope, are not recorded.
+        let internal = fvk.address_at(0u32, Scope::Internal);
+        assert_eq!(generator.mark_funded(&internal), None);
+        assert_eq!(generator.issued().count(), 4);
+    }
+
+    #[test]
+    fn enforces_the_gap_limit() {
+        let (_, generator) = generator();
+        let mut generator = generator.with_gap_limit(2);
+        let (_, first) = generator.issue().unwrap();
+        generator.issue().unwrap();
+        assert_eq!(generator.issue(), Err(Error::GapLimit(2)));
+
+        // Funds received at the first address allow one more to be issued.
+        generator.mark_funded(&first);
+        assert_eq!(
+            generator.issue().map(|(j, _)| j),
+            Ok(DiversifierIndex::from(2u32))
+        );
+        assert_eq!(generator.issue(), Err(Error::GapLimit(2)));
+    }
+
+    #[test]
+    fn recovers_addresses_issued_elsewhere() {
+        // A restored wallet learns of an address from its scanner.
+        let (fvk, mut generator) = generator();
+        let address = fvk.address_at(10u32, Scope::External);
+        assert_eq!(
+            generator.mark_funded(&address),
+            Some(DiversifierIndex::from(10u32))
+        );
+        assert!(generator.is_issued(DiversifierIndex::from(10u32)));
+        assert_eq!(generator.next_index(), Some(DiversifierIndex::from(11u32)));
+        assert_eq!(
+            generator.issue().map(|(j, _)| j),
+            Ok(DiversifierIndex::from(11u32))
+        );
+    }
+}
@@ -20,12 +20,22 @@ mod action;
 mod address;
 pub mod builder;
 pub mod bundle;
+#[cfg(any(test, feature = "test-dependencies"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-dependencies")))]
+pub mod chain;
 pub mod circuit;
 mod constants;
+pub mod fees;
+#[cfg(feature = "zsa")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zsa")))]
+pub mod issuance;
 pub mod keys;
 pub mod note;
 pub mod note_encryption;
 pub mod primitives;
+pub mod prover;
+#[cfg(feature = "serde-types")]
+mod serialization;
 mod spec;
 pub mod tree;
 pub mod value;
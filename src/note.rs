@@ -1,5 +1,6 @@
 //! Data structures used for note construction.
 use core::fmt;
+use std::io::{self, Read, Write};
 
 use group::GroupEncoding;
 use pasta_curves::pallas;
@@ -8,7 +9,7 @@ use subtle::CtOption;
 
 use crate::{
     keys::{EphemeralSecretKey, FullViewingKey, Scope, SpendingKey},
-    spec::{to_base, to_scalar, NonZeroPallasScalar, PrfExpand},
+    spec::{to_base, to_scalar, NonZeroPallasScalar, PrfExpand, SecretField},
     value::NoteValue,
     Address,
 };
@@ -19,9 +20,23 @@ pub use self::commitment::{ExtractedNoteCommitment, NoteCommitment};
 pub(crate) mod nullifier;
 pub use self::nullifier::Nullifier;
 
+#[cfg(feature = "zsa")]
+mod asset_base;
+#[cfg(feature = "zsa")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zsa")))]
+pub use self::asset_base::{AssetBase, MAX_ASSET_DESCRIPTION_SIZE};
+
+/// The lead byte of the encoding of a [`Note`], matching the lead byte of its note
+/// plaintext.
+const NOTE_ENCODING_VERSION: u8 = 0x02;
+
+/// The lead byte of the encoding of a [`Note`] of a non-native asset.
+#[cfg(feature = "zsa")]
+const NOTE_ENCODING_VERSION_ZSA: u8 = 0x03;
+
 /// The ZIP 212 seed randomness for a note.
 #[derive(Copy, Clone, Debug)]
-pub(crate) struct RandomSeed([u8; 32]);
+pub struct RandomSeed([u8; 32]);
 
 impl RandomSeed {
     pub(crate) fn random(rng: &mut impl RngCore, rho: &Nullifier) -> Self {
@@ -35,13 +50,17 @@ impl RandomSeed {
         }
     }
 
-    pub(crate) fn from_bytes(rseed: [u8; 32], rho: &Nullifier) -> CtOption<Self> {
+    /// Reads a note's random seed from bytes, given the note's nullifier.
+    ///
+    /// Returns `None` if the nullifier is not for the same note as the seed.
+    pub fn from_bytes(rseed: [u8; 32], rho: &Nullifier) -> CtOption<Self> {
         let rseed = RandomSeed(rseed);
         let esk = rseed.esk_inner(rho);
         CtOption::new(rseed, esk.is_some())
     }
 
-    pub(crate) fn as_bytes(&self) -> &[u8; 32] {
+    /// Returns the byte array corresponding to this seed.
+    pub fn as_bytes(&self) -> &[u8; 32] {
         &self.0
     }
 
@@ -73,9 +92,9 @@ impl RandomSeed {
     ///
     /// [orchardsend]: https://zips.z.cash/protocol/nu5.pdf#orchardsend
     pub(crate) fn rcm(&self, rho: &Nullifier) -> commitment::NoteCommitTrapdoor {
-        commitment::NoteCommitTrapdoor(to_scalar(
+        commitment::NoteCommitTrapdoor(SecretField::new(to_scalar(
             PrfExpand::Rcm.with_ad(&self.0, &rho.to_bytes()[..]),
-        ))
+        )))
     }
 }
 
@@ -95,6 +114,9 @@ pub struct Note {
     rho: Nullifier,
     /// The seed randomness for various note components.
     rseed: RandomSeed,
+    /// The asset of this note.
+    #[cfg(feature = "zsa")]
+    asset: AssetBase,
 }
 
 impl PartialEq for Note {
@@ -108,18 +130,61 @@ impl PartialEq for Note {
 impl Eq for Note {}
 
 impl Note {
-    pub(crate) fn from_parts(
+    /// Creates a `Note` from its component parts.
+    ///
+    /// Returns `None` if a valid [`NoteCommitment`] cannot be derived from the note, or
+    /// if `rseed` is not a valid random seed for a note with the given `rho`.
+    ///
+    /// # Caveats
+    ///
+    /// This low-level constructor enforces that the provided arguments produce an
+    /// internally valid `Note`. However, it allows notes to be constructed in a way that
+    /// violates required security checks for note decryption, as specified in
+    /// [Section 4.19] of the Zcash Protocol Specification. Users of this constructor
+    /// should only call it with note components that have been fully validated by
+    /// decrypting a received note according to [Section 4.19].
+    ///
+    /// [Section 4.19]: https://zips.z.cash/protocol/protocol.pdf#saplingandorchardinband
+    pub fn from_parts(
         recipient: Address,
         value: NoteValue,
         rho: Nullifier,
         rseed: RandomSeed,
-    ) -> Self {
+    ) -> CtOption<Self> {
         Note {
             recipient,
             value,
             rho,
             rseed,
+            #[cfg(feature = "zsa")]
+            asset: AssetBase::native(),
         }
+        .validated()
+    }
+
+    #[cfg(feature = "zsa")]
+    pub(crate) fn from_parts_with_asset(
+        recipient: Address,
+        value: NoteValue,
+        asset: AssetBase,
+        rho: Nullifier,
+        rseed: RandomSeed,
+    ) -> CtOption<Self> {
+        Note {
+            recipient,
+            value,
+            rho,
+            rseed,
+            asset,
+        }
+        .validated()
+    }
+
+    /// Checks that `rseed` is valid for `rho`, and that the note has a commitment.
+    fn validated(self) -> CtOption<Self> {
+        let rseed_valid = RandomSeed::from_bytes(self.rseed.0, &self.rho).is_some();
+        let commitment_valid = self.commitment_inner().is_some();
+        CtOption::new(self, rseed_valid & commitment_valid)
     }
 
     /// Generates a new note.
@@ -139,6 +204,35 @@ impl Note {
                 value,
                 rho,
                 rseed: RandomSeed::random(&mut rng, &rho),
+                #[cfg(feature = "zsa")]
+                asset: AssetBase::native(),
+            };
+            if note.commitment_inner().is_some().into() {
+                break note;
+            }
+        }
+    }
+
+    /// Generates a new note of the given asset.
+    ///
+    /// Defined in [ZIP 226: Transfer and Burn of Zcash Shielded Assets][notestructure].
+    ///
+    /// [notestructure]: https://zips.z.cash/zip-0226#note-structure-commitment
+    #[cfg(feature = "zsa")]
+    pub(crate) fn new_with_asset(
+        recipient: Address,
+        value: NoteValue,
+        asset: AssetBase,
+        rho: Nullifier,
+        mut rng: impl RngCore,
+    ) -> Self {
+        loop {
+            let note = Note {
+                recipient,
+                value,
+                rho,
+                rseed: RandomSeed::random(&mut rng, &rho),
+                asset,
             };
             if note.commitment_inner().is_some().into() {
                 break note;
@@ -179,8 +273,15 @@ impl Note {
         self.value
     }
 
+    /// Returns the asset of this note.
+    #[cfg(feature = "zsa")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "zsa")))]
+    pub fn asset(&self) -> AssetBase {
+        self.asset
+    }
+
     /// Returns the rseed value of this note.
-    pub(crate) fn rseed(&self) -> &RandomSeed {
+    pub fn rseed(&self) -> &RandomSeed {
         &self.rseed
     }
 
@@ -216,6 +317,19 @@ impl Note {
     fn commitment_inner(&self) -> CtOption<NoteCommitment> {
         let g_d = self.recipient.g_d();
 
+        #[cfg(feature = "zsa")]
+        if !bool::from(self.asset.is_native()) {
+            return NoteCommitment::derive_zsa(
+                g_d.to_bytes(),
+                self.recipient.pk_d().to_bytes(),
+                self.value,
+                self.rho.0,
+                self.rseed.psi(&self.rho),
+                self.asset.to_bytes(),
+                self.rseed.rcm(&self.rho),
+            );
+        }
+
         NoteCommitment::derive(
             g_d.to_bytes(),
             self.recipient.pk_d().to_bytes(),
@@ -235,6 +349,81 @@ impl Note {
             self.commitment(),
         )
     }
+
+    /// Serializes this note, for example for storage in a wallet database.
+    ///
+    /// The encoding starts with the same lead byte as the note's plaintext, followed by
+    /// the raw encoding of the recipient address, the little-endian value, rho and
+    /// rseed. Notes of non-native assets are followed by their asset base.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        #[cfg(feature = "zsa")]
+        if !bool::from(self.asset.is_native()) {
+            writer.write_all(&[NOTE_ENCODING_VERSION_ZSA])?;
+            self.write_parts(&mut writer)?;
+            return writer.write_all(&self.asset.to_bytes());
+        }
+
+        writer.write_all(&[NOTE_ENCODING_VERSION])?;
+        self.write_parts(&mut writer)
+    }
+
+    fn write_parts<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&self.recipient.to_raw_address_bytes())?;
+        writer.write_all(&self.value.to_bytes())?;
+        writer.write_all(&self.rho.to_bytes())?;
+        writer.write_all(&self.rseed.0)
+    }
+
+    /// Parses a note from the encoding produced by [`Note::write`].
+    ///
+    /// The same caveats apply as for [`Note::from_parts`].
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_owned());
+
+        let mut version = [0u8; 1];
+        reader.read_exact(&mut version)?;
+        match version[0] {
+            NOTE_ENCODING_VERSION => (),
+            #[cfg(feature = "zsa")]
+            NOTE_ENCODING_VERSION_ZSA => (),
+            _ => return Err(invalid("Unsupported Orchard note encoding version")),
+        }
+
+        let mut recipient = [0u8; 43];
+        reader.read_exact(&mut recipient)?;
+        let recipient = Option::from(Address::from_raw_address_bytes(&recipient))
+            .ok_or_else(|| invalid("Invalid Orchard note recipient"))?;
+
+        let mut value = [0u8; 8];
+        reader.read_exact(&mut value)?;
+        let value = NoteValue::from_bytes(value);
+
+        let mut rho = [0u8; 32];
+        reader.read_exact(&mut rho)?;
+        let rho = Option::from(Nullifier::from_bytes(&rho))
+            .ok_or_else(|| invalid("Invalid Orchard note rho"))?;
+
+        let mut rseed = [0u8; 32];
+        reader.read_exact(&mut rseed)?;
+        let rseed = Option::from(RandomSeed::from_bytes(rseed, &rho))
+            .ok_or_else(|| invalid("Invalid Orchard note rseed"))?;
+
+        #[cfg(feature = "zsa")]
+        if version[0] == NOTE_ENCODING_VERSION_ZSA {
+            let mut asset = [0u8; 32];
+            reader.read_exact(&mut asset)?;
+            let asset = Option::<AssetBase>::from(AssetBase::from_bytes(&asset))
+                .filter(|asset| !bool::from(asset.is_native()))
+                .ok_or_else(|| invalid("Invalid Orchard note asset"))?;
+            return Option::from(Note::from_parts_with_asset(
+                recipient, value, asset, rho, rseed,
+            ))
+            .ok_or_else(|| invalid("Invalid Orchard note"));
+        }
+
+        Option::from(Note::from_parts(recipient, value, rho, rseed))
+            .ok_or_else(|| invalid("Invalid Orchard note"))
+    }
 }
 
 /// An encrypted note.
@@ -259,6 +448,57 @@ impl fmt::Debug for TransmittedNoteCiphertext {
     }
 }
 
+impl TransmittedNoteCiphertext {
+    /// Returns the memo chunk of the encrypted note ciphertext, which ZIP 244 commits to
+    /// separately from the rest of the action.
+    pub fn memo_chunk(&self) -> &[u8] {
+        &self.enc_ciphertext[52..564]
+    }
+
+    /// Discards the memo chunk of this ciphertext.
+    pub fn prune(&self) -> PrunedNoteCiphertext {
+        PrunedNoteCiphertext {
+            epk_bytes: self.epk_bytes,
+            enc_ciphertext_compact: self.enc_ciphertext[..52].try_into().unwrap(),
+            enc_ciphertext_tag: self.enc_ciphertext[564..].try_into().unwrap(),
+            out_ciphertext: self.out_ciphertext,
+        }
+    }
+}
+
+/// An encrypted note without its memo.
+///
+/// This keeps the parts of a [`TransmittedNoteCiphertext`] that are needed to recompute
+/// a bundle commitment, given the digest of the bundle's memos. The compact portion can
+/// still be decrypted to recover the note, but its authentication tag cannot be checked.
+#[derive(Clone)]
+pub struct PrunedNoteCiphertext {
+    /// The serialization of the ephemeral public key
+    pub epk_bytes: [u8; 32],
+    /// The first 52 bytes of the encrypted note ciphertext, which encrypt the note
+    /// plaintext without its memo
+    pub enc_ciphertext_compact: [u8; 52],
+    /// The authentication tag at the end of the encrypted note ciphertext
+    pub enc_ciphertext_tag: [u8; 16],
+    /// An encrypted value that allows the holder of the outgoing cipher
+    /// key for the note to recover the note plaintext.
+    pub out_ciphertext: [u8; 80],
+}
+
+impl fmt::Debug for PrunedNoteCiphertext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrunedNoteCiphertext")
+            .field("epk_bytes", &self.epk_bytes)
+            .field(
+                "enc_ciphertext_compact",
+                &hex::encode(self.enc_ciphertext_compact),
+            )
+            .field("enc_ciphertext_tag", &hex::encode(self.enc_ciphertext_tag))
+            .field("out_ciphertext", &hex::encode(self.out_ciphertext))
+            .finish()
+    }
+}
+
 /// Generators for property testing.
 #[cfg(any(test, feature = "test-dependencies"))]
 #[cfg_attr(docsrs, doc(cfg(feature = "test-dependencies")))]
@@ -290,7 +530,58 @@ pub mod testing {
                 value,
                 rho,
                 rseed,
+                #[cfg(feature = "zsa")]
+                asset: super::AssetBase::native(),
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use rand::rngs::OsRng;
+
+    use super::{testing::arb_note, Note, RandomSeed};
+    use crate::{note::Nullifier, value::NoteValue};
+
+    proptest! {
+        #[test]
+        fn note_encoding_round_trip(note in arb_note(NoteValue::from_raw(10_000))) {
+            let mut encoded = vec![];
+            note.write(&mut encoded).unwrap();
+            assert_eq!(encoded.len(), 116);
+
+            let decoded = Note::read(&encoded[..]).unwrap();
+            assert_eq!(decoded, note);
+            assert_eq!(decoded.rseed().as_bytes(), note.rseed().as_bytes());
+
+            let reconstructed =
+                Note::from_parts(note.recipient(), note.value(), note.rho(), *note.rseed());
+            assert_eq!(reconstructed.unwrap(), note);
+        }
+    }
+
+    #[test]
+    fn note_encoding_rejects_invalid() {
+        let mut rng = OsRng;
+        let (_, _, note) = Note::dummy(&mut rng, None);
+
+        let mut encoded = vec![];
+        note.write(&mut encoded).unwrap();
+
+        // Unknown versions are rejected.
+        let mut bad_version = encoded.clone();
+        bad_version[0] = 0x01;
+        assert!(Note::read(&bad_version[..]).is_err());
+
+        // Truncated encodings are rejected.
+        assert!(Note::read(&encoded[..encoded.len() - 1]).is_err());
+
+        // Notes can be constructed from a random seed generated for their rho.
+        let rho = Nullifier::dummy(&mut rng);
+        let rseed = RandomSeed::random(&mut rng, &rho);
+        let note = Note::from_parts(note.recipient(), note.value(), rho, rseed).unwrap();
+        assert_eq!(note.rho(), rho);
+    }
+}
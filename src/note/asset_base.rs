@@ -0,0 +1,131 @@
+//! Asset identifiers for Zcash Shielded Assets.
+
+use blake2b_simd::Params;
+use group::{Group, GroupEncoding};
+use halo2_proofs::arithmetic::CurveExt;
+use pasta_curves::pallas;
+use subtle::{Choice, ConstantTimeEq, CtOption};
+
+use crate::{constants::fixed_bases::value_commit_v, keys::IssuanceValidatingKey};
+
+/// The personalization for deriving the base point of a non-native asset.
+const ZSA_ASSET_BASE_PERSONALIZATION: &str = "z.cash:OrchardZSA";
+
+/// The personalization for hashing an asset identifier to an asset digest.
+const ZSA_ASSET_DIGEST_PERSONALIZATION: &[u8; 16] = b"ZSA-Asset-Digest";
+
+/// The maximum length in bytes of an asset description.
+pub const MAX_ASSET_DESCRIPTION_SIZE: usize = 512;
+
+/// The base point used in value commitments to notes of a particular asset.
+///
+/// The native asset (ZEC) uses $\mathcal{V}^{\mathsf{Orchard}}$, so value commitments to
+/// native notes are unchanged from NU5. Every other asset is identified by the issuance
+/// validating key of its issuer together with an asset description, following
+/// [ZIP 227: Issuance of Zcash Shielded Assets][zip227].
+///
+/// [zip227]: https://zips.z.cash/zip-0227
+#[derive(Clone, Copy, Debug)]
+pub struct AssetBase(pallas::Point);
+
+impl PartialEq for AssetBase {
+    fn eq(&self, other: &Self) -> bool {
+        bool::from(self.0.ct_eq(&other.0))
+    }
+}
+
+impl Eq for AssetBase {}
+
+impl AssetBase {
+    /// Returns the base point for the native asset, ZEC.
+    pub fn native() -> Self {
+        // The precomputed generator avoids hashing to the curve on every call.
+        AssetBase(value_commit_v::generator().into())
+    }
+
+    /// Derives the base point for the asset with description `asset_desc` issued under
+    /// the issuance validating key `ik`.
+    ///
+    /// Returns `None` if `asset_desc` is empty or longer than
+    /// [`MAX_ASSET_DESCRIPTION_SIZE`] bytes.
+    ///
+    /// Defined in [ZIP 227: Issuance of Zcash Shielded Assets][assetidentifier].
+    ///
+    /// [assetidentifier]: https://zips.z.cash/zip-0227#asset-identifiers
+    pub fn derive(ik: &IssuanceValidatingKey, asset_desc: &str) -> Option<Self> {
+        if asset_desc.is_empty() || asset_desc.len() > MAX_ASSET_DESCRIPTION_SIZE {
+            return None;
+        }
+
+        // EncodeAssetId(ik, asset_desc) = 0x00 || ik || asset_desc
+        let asset_digest = Params::new()
+            .hash_length(64)
+            .personal(ZSA_ASSET_DIGEST_PERSONALIZATION)
+            .to_state()
+            .update(&[0x00])
+            .update(&ik.to_bytes())
+            .update(asset_desc.as_bytes())
+            .finalize();
+
+        Some(AssetBase(pallas::Point::hash_to_curve(
+            ZSA_ASSET_BASE_PERSONALIZATION,
+        )(asset_digest.as_bytes())))
+    }
+
+    /// Returns whether this is the base point of the native asset.
+    pub fn is_native(&self) -> Choice {
+        self.0.ct_eq(&Self::native().0)
+    }
+
+    /// Parses an asset base from its byte encoding.
+    ///
+    /// Returns `None` if the encoding is not a canonical encoding of a non-identity
+    /// Pallas point.
+    pub fn from_bytes(bytes: &[u8; 32]) -> CtOption<Self> {
+        pallas::Point::from_bytes(bytes).and_then(|p| CtOption::new(AssetBase(p), !p.is_identity()))
+    }
+
+    /// Returns the byte encoding of this asset base.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    /// Returns the point used as the value base in value commitments.
+    pub(crate) fn cv_base(&self) -> pallas::Point {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::{arithmetic::CurveExt, pallas};
+
+    use super::{AssetBase, MAX_ASSET_DESCRIPTION_SIZE};
+    use crate::{
+        constants::fixed_bases::{VALUE_COMMITMENT_PERSONALIZATION, VALUE_COMMITMENT_V_BYTES},
+        keys::{IssuanceAuthorizingKey, IssuanceValidatingKey},
+    };
+
+    #[test]
+    fn asset_base_derivation() {
+        let isk = IssuanceAuthorizingKey::from_zip32_seed(&[0; 32], 133, 0).unwrap();
+        let ik = IssuanceValidatingKey::from(&isk);
+
+        let a = AssetBase::derive(&ik, "Asset A").unwrap();
+        let b = AssetBase::derive(&ik, "Asset B").unwrap();
+        assert_ne!(a, b);
+        assert_eq!(a, AssetBase::derive(&ik, "Asset A").unwrap());
+        assert!(!bool::from(a.is_native()));
+        assert!(bool::from(AssetBase::native().is_native()));
+        assert_eq!(
+            AssetBase::native(),
+            AssetBase(pallas::Point::hash_to_curve(
+                VALUE_COMMITMENT_PERSONALIZATION
+            )(&VALUE_COMMITMENT_V_BYTES))
+        );
+        assert_eq!(AssetBase::from_bytes(&a.to_bytes()).unwrap(), a);
+
+        assert!(AssetBase::derive(&ik, "").is_none());
+        assert!(AssetBase::derive(&ik, &"x".repeat(MAX_ASSET_DESCRIPTION_SIZE + 1)).is_none());
+    }
+}
@@ -1,23 +1,54 @@
 use core::iter;
 
 use bitvec::{array::BitArray, order::Lsb0};
-use group::ff::{PrimeField, PrimeFieldBits};
+use group::{
+    ff::{PrimeField, PrimeFieldBits},
+    GroupEncoding,
+};
 use halo2_gadgets::sinsemilla::primitives as sinsemilla;
 use pasta_curves::pallas;
 use subtle::{ConstantTimeEq, CtOption};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use crate::{
     constants::{fixed_bases::NOTE_COMMITMENT_PERSONALIZATION, L_ORCHARD_BASE},
-    spec::extract_p,
+    spec::{extract_p, SecretField},
     value::NoteValue,
 };
 
+/// The personalization for note commitments to notes of a non-native asset.
+#[cfg(feature = "zsa")]
+const ZSA_NOTE_COMMITMENT_PERSONALIZATION: &str = "z.cash:ZSA-NoteCommit";
+
+/// The trapdoor of a note commitment, which is zeroized when it is dropped.
 #[derive(Clone, Debug)]
-pub(crate) struct NoteCommitTrapdoor(pub(super) pallas::Scalar);
+pub(crate) struct NoteCommitTrapdoor(pub(super) SecretField<pallas::Scalar>);
+
+impl Zeroize for NoteCommitTrapdoor {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for NoteCommitTrapdoor {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for NoteCommitTrapdoor {}
 
 impl NoteCommitTrapdoor {
     pub(crate) fn inner(&self) -> pallas::Scalar {
-        self.0
+        *self.0
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8; 32]) -> CtOption<Self> {
+        pallas::Scalar::from_repr(*bytes).map(|rcm| NoteCommitTrapdoor(SecretField::new(rcm)))
+    }
+
+    pub(crate) fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_repr()
     }
 }
 
@@ -29,6 +60,14 @@ impl NoteCommitment {
     pub(crate) fn inner(&self) -> pallas::Point {
         self.0
     }
+
+    pub(crate) fn from_bytes(bytes: &[u8; 32]) -> CtOption<Self> {
+        pallas::Point::from_bytes(bytes).map(NoteCommitment)
+    }
+
+    pub(crate) fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
 }
 
 impl NoteCommitment {
@@ -58,6 +97,40 @@ impl NoteCommitment {
             )
             .map(NoteCommitment)
     }
+
+    /// $NoteCommit^{OrchardZSA}$ for notes of a non-native asset.
+    ///
+    /// This is a Sinsemilla commitment in a separate domain, which additionally commits
+    /// to the note's asset base. Notes of the native asset use [`NoteCommitment::derive`],
+    /// so that their commitments are unchanged from NU5.
+    ///
+    /// Defined in [ZIP 226: Transfer and Burn of Zcash Shielded Assets][notestructure].
+    ///
+    /// [notestructure]: https://zips.z.cash/zip-0226#note-structure-commitment
+    #[cfg(feature = "zsa")]
+    pub(super) fn derive_zsa(
+        g_d: [u8; 32],
+        pk_d: [u8; 32],
+        v: NoteValue,
+        rho: pallas::Base,
+        psi: pallas::Base,
+        asset: [u8; 32],
+        rcm: NoteCommitTrapdoor,
+    ) -> CtOption<Self> {
+        let domain = sinsemilla::CommitDomain::new(ZSA_NOTE_COMMITMENT_PERSONALIZATION);
+        domain
+            .commit(
+                iter::empty()
+                    .chain(BitArray::<_, Lsb0>::new(g_d).iter().by_vals())
+                    .chain(BitArray::<_, Lsb0>::new(pk_d).iter().by_vals())
+                    .chain(v.to_le_bits().iter().by_vals())
+                    .chain(rho.to_le_bits().iter().by_vals().take(L_ORCHARD_BASE))
+                    .chain(psi.to_le_bits().iter().by_vals().take(L_ORCHARD_BASE))
+                    .chain(BitArray::<_, Lsb0>::new(asset).iter().by_vals()),
+                &rcm.0,
+            )
+            .map(NoteCommitment)
+    }
 }
 
 /// The x-coordinate of the commitment to a note.
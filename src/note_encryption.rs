@@ -22,8 +22,32 @@ use crate::{
     Address, Note,
 };
 
+#[cfg(feature = "zsa")]
+use crate::note::AssetBase;
+
 const PRF_OCK_ORCHARD_PERSONALIZATION: &[u8; 16] = b"Zcash_Orchardock";
 
+/// The lead byte of note plaintexts for notes of a non-native asset.
+///
+/// `zcash_note_encryption` fixes the note plaintext size to that of NU5, so a version 3
+/// plaintext stores the 32-byte asset base immediately after the compact note plaintext
+/// and truncates the memo to [`ZSA_MEMO_SIZE`] bytes. As the asset base is not part of the
+/// compact note plaintext, version 3 notes can only be decrypted from full ciphertexts.
+///
+/// This is not the version 3 plaintext of ZIP 226, which is longer than an NU5 plaintext
+/// so that it can carry both the asset base and a full 512-byte memo.
+#[cfg(feature = "zsa")]
+const ZSA_NOTE_PLAINTEXT_VERSION: u8 = 0x03;
+
+/// The end of the asset base within a version 3 note plaintext.
+#[cfg(feature = "zsa")]
+const ZSA_ASSET_END: usize = COMPACT_NOTE_SIZE + 32;
+
+/// The maximum size of the memo of a note of a non-native asset.
+#[cfg(feature = "zsa")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zsa")))]
+pub const ZSA_MEMO_SIZE: usize = NOTE_PLAINTEXT_SIZE - ZSA_ASSET_END;
+
 /// Defined in [Zcash Protocol Spec § 5.4.2: Pseudo Random Functions][concreteprfs].
 ///
 /// [concreteprfs]: https://zips.z.cash/protocol/nu5.pdf#concreteprfs
@@ -60,9 +84,27 @@ where
     assert!(plaintext.len() >= COMPACT_NOTE_SIZE);
 
     // Check note plaintext version
+    #[cfg(not(feature = "zsa"))]
     if plaintext[0] != 0x02 {
         return None;
     }
+    #[cfg(feature = "zsa")]
+    let asset = match plaintext[0] {
+        0x02 => AssetBase::native(),
+        ZSA_NOTE_PLAINTEXT_VERSION if plaintext.len() >= ZSA_ASSET_END => {
+            let asset = Option::from(AssetBase::from_bytes(
+                plaintext[COMPACT_NOTE_SIZE..ZSA_ASSET_END]
+                    .try_into()
+                    .unwrap(),
+            ))?;
+            // The native asset must be encoded with a version 2 plaintext.
+            if bool::from(asset.is_native()) {
+                return None;
+            }
+            asset
+        }
+        _ => return None,
+    };
 
     // The unwraps below are guaranteed to succeed by the assertion above
     let diversifier = Diversifier::from_bytes(plaintext[1..12].try_into().unwrap());
@@ -75,7 +117,12 @@ where
     let pk_d = get_validated_pk_d(&diversifier)?;
 
     let recipient = Address::from_parts(diversifier, pk_d);
-    let note = Note::from_parts(recipient, value, domain.rho, rseed);
+    #[cfg(not(feature = "zsa"))]
+    let note = Option::from(Note::from_parts(recipient, value, domain.rho, rseed))?;
+    #[cfg(feature = "zsa")]
+    let note = Option::from(Note::from_parts_with_asset(
+        recipient, value, asset, domain.rho, rseed,
+    ))?;
     Some((note, recipient))
 }
 
@@ -152,6 +199,15 @@ impl Domain for OrchardDomain {
         np[1..12].copy_from_slice(note.recipient().diversifier().as_array());
         np[12..20].copy_from_slice(&note.value().to_bytes());
         np[20..52].copy_from_slice(note.rseed().as_bytes());
+        #[cfg(feature = "zsa")]
+        if !bool::from(note.asset().is_native()) {
+            np[0] = ZSA_NOTE_PLAINTEXT_VERSION;
+            np[COMPACT_NOTE_SIZE..ZSA_ASSET_END].copy_from_slice(&note.asset().to_bytes());
+            // The builder rejects memos that do not fit.
+            debug_assert!(memo[ZSA_MEMO_SIZE..].iter().all(|b| *b == 0));
+            np[ZSA_ASSET_END..].copy_from_slice(&memo[..ZSA_MEMO_SIZE]);
+            return NotePlaintextBytes(np);
+        }
         np[52..].copy_from_slice(memo);
         NotePlaintextBytes(np)
     }
@@ -219,6 +275,12 @@ impl Domain for OrchardDomain {
     }
 
     fn extract_memo(&self, plaintext: &NotePlaintextBytes) -> Self::Memo {
+        #[cfg(feature = "zsa")]
+        if plaintext.0[0] == ZSA_NOTE_PLAINTEXT_VERSION {
+            let mut memo = [0; 512];
+            memo[..ZSA_MEMO_SIZE].copy_from_slice(&plaintext.0[ZSA_ASSET_END..NOTE_PLAINTEXT_SIZE]);
+            return memo;
+        }
         plaintext.0[COMPACT_NOTE_SIZE..NOTE_PLAINTEXT_SIZE]
             .try_into()
             .unwrap()
@@ -369,7 +431,7 @@ mod tests {
             assert_eq!(ock.as_ref(), tv.ock);
 
             let recipient = Address::from_parts(d, pk_d);
-            let note = Note::from_parts(recipient, value, rho, rseed);
+            let note = Note::from_parts(recipient, value, rho, rseed).unwrap();
             assert_eq!(ExtractedNoteCommitment::from(note.commitment()), cmx);
 
             let action = Action::from_parts(
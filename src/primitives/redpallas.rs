@@ -4,6 +4,7 @@ use core::cmp::{Ord, Ordering, PartialOrd};
 
 use pasta_curves::pallas;
 use rand::{CryptoRng, RngCore};
+use zeroize::{DefaultIsZeroes, Zeroize, ZeroizeOnDrop};
 
 pub use reddsa::batch;
 
@@ -23,17 +24,57 @@ impl SigType for Binding {}
 
 /// A RedPallas signing key.
 #[derive(Clone, Debug)]
-pub struct SigningKey<T: SigType>(reddsa::SigningKey<T>);
+pub struct SigningKey<T: SigType>(SecretSigningKey<T>);
+
+/// A `reddsa` signing key, which is reset to the signing key for the zero scalar when
+/// zeroized.
+///
+/// `reddsa` signing keys are `Copy`, and so cannot clear themselves when they are
+/// dropped. This plays the role of `SecretField` for them.
+#[derive(Debug)]
+struct SecretSigningKey<T: SigType>(reddsa::SigningKey<T>);
+
+impl<T: SigType> Clone for SecretSigningKey<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: SigType> Copy for SecretSigningKey<T> {}
+
+impl<T: SigType> Default for SecretSigningKey<T> {
+    fn default() -> Self {
+        SecretSigningKey(
+            reddsa::SigningKey::try_from([0; 32]).expect("zero is a canonical scalar encoding"),
+        )
+    }
+}
+
+impl<T: SigType> DefaultIsZeroes for SecretSigningKey<T> {}
+
+impl<T: SigType> Zeroize for SigningKey<T> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: SigType> Drop for SigningKey<T> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<T: SigType> ZeroizeOnDrop for SigningKey<T> {}
 
 impl<T: SigType> From<SigningKey<T>> for [u8; 32] {
     fn from(sk: SigningKey<T>) -> [u8; 32] {
-        sk.0.into()
+        (&sk).into()
     }
 }
 
 impl<T: SigType> From<&SigningKey<T>> for [u8; 32] {
     fn from(sk: &SigningKey<T>) -> [u8; 32] {
-        sk.0.into()
+        sk.0 .0.into()
     }
 }
 
@@ -41,7 +82,7 @@ impl<T: SigType> TryFrom<[u8; 32]> for SigningKey<T> {
     type Error = reddsa::Error;
 
     fn try_from(bytes: [u8; 32]) -> Result<Self, Self::Error> {
-        bytes.try_into().map(SigningKey)
+        reddsa::SigningKey::try_from(bytes).map(|sk| SigningKey(SecretSigningKey(sk)))
     }
 }
 
@@ -50,14 +91,14 @@ impl SigningKey<SpendAuth> {
     ///
     /// Randomization is only supported for `SpendAuth` keys.
     pub fn randomize(&self, randomizer: &pallas::Scalar) -> Self {
-        SigningKey(self.0.randomize(randomizer))
+        SigningKey(SecretSigningKey(self.0 .0.randomize(randomizer)))
     }
 }
 
 impl<T: SigType> SigningKey<T> {
     /// Creates a signature of type `T` on `msg` using this `SigningKey`.
     pub fn sign<R: RngCore + CryptoRng>(&self, rng: R, msg: &[u8]) -> Signature<T> {
-        Signature(self.0.sign(rng, msg))
+        Signature(self.0 .0.sign(rng, msg))
     }
 }
 
@@ -87,7 +128,7 @@ impl<T: SigType> TryFrom<[u8; 32]> for VerificationKey<T> {
 
 impl<'a, T: SigType> From<&'a SigningKey<T>> for VerificationKey<T> {
     fn from(sk: &'a SigningKey<T>) -> VerificationKey<T> {
-        VerificationKey((&sk.0).into())
+        VerificationKey((&sk.0 .0).into())
     }
 }
 
@@ -194,7 +235,7 @@ pub mod testing {
                 .prop_map(reddsa::SigningKey::try_from)
                 .prop_filter("Values must be parseable as valid signing keys", |r| r.is_ok())
         ) -> SigningKey<SpendAuth> {
-            SigningKey(sk.unwrap())
+            SigningKey::from_inner(sk.unwrap())
         }
     }
 
@@ -205,7 +246,7 @@ pub mod testing {
                 .prop_map(reddsa::SigningKey::try_from)
                 .prop_filter("Values must be parseable as valid signing keys", |r| r.is_ok())
         ) -> SigningKey<Binding> {
-            SigningKey(sk.unwrap())
+            SigningKey::from_inner(sk.unwrap())
         }
     }
 
@@ -0,0 +1,280 @@
+//! Proving of Orchard bundles by a separate process.
+//!
+//! A [`ProvingRequest`] can be proven by any [`Prover`]: either locally with a
+//! [`ProvingKey`], or by sending it to a prover service such as the `orchard-prover`
+//! binary with a [`Client`]. The proof is then added to the bundle with
+//! [`Bundle::create_proof_with`].
+//!
+//! # Protocol
+//!
+//! The client and the service exchange frames, each of which is a little-endian `u32`
+//! length followed by that many bytes. The client sends a request frame containing a
+//! [`ProvingRequest`] encoded with [`ProvingRequest::write`], and the service replies
+//! with a response frame whose first byte is [`RESPONSE_OK`] followed by the proof, or
+//! [`RESPONSE_ERROR`] followed by a UTF-8 error message. A connection can carry any
+//! number of requests, one at a time. Frames that exceed [`MAX_REQUEST_SIZE`] or
+//! [`MAX_RESPONSE_SIZE`] are rejected without being read.
+//!
+//! [`Bundle::create_proof_with`]: crate::Bundle::create_proof_with
+
+use std::fmt;
+use std::io::{self, Read, Write};
+#[cfg(unix)]
+use std::{os::unix::net::UnixStream, path::Path};
+
+use halo2_proofs::plonk;
+use rand::rngs::OsRng;
+
+use crate::circuit::{Proof, ProvingKey, ProvingRequest};
+
+/// The largest request frame that a prover service will accept, in bytes.
+///
+/// This allows requests for several hundred actions.
+pub const MAX_REQUEST_SIZE: u32 = 1 << 20;
+
+/// The largest response frame that a [`Client`] will accept, in bytes.
+pub const MAX_RESPONSE_SIZE: u32 = 1 << 22;
+
+/// The first byte of a response frame containing a proof.
+pub const RESPONSE_OK: u8 = 0;
+
+/// The first byte of a response frame containing an error message.
+pub const RESPONSE_ERROR: u8 = 1;
+
+/// Errors that can occur while proving with a [`Prover`].
+#[derive(Debug)]
+pub enum Error {
+    /// An I/O error occurred while communicating with a prover service.
+    Io(io::Error),
+    /// A frame was larger than the maximum allowed size.
+    FrameTooLarge(u32),
+    /// The response from a prover service was malformed.
+    InvalidResponse,
+    /// The prover service could not create a proof, for the given reason.
+    Remote(String),
+    /// An error occurred while creating a proof locally.
+    Proof(plonk::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::FrameTooLarge(len) => write!(f, "Frame of {} bytes is too large", len),
+            Error::InvalidResponse => f.write_str("Invalid response from prover"),
+            Error::Remote(msg) => write!(f, "Prover failed: {}", msg),
+            Error::Proof(e) => write!(f, "Proof error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<plonk::Error> for Error {
+    fn from(e: plonk::Error) -> Self {
+        Error::Proof(e)
+    }
+}
+
+/// Something that can create proofs for [`ProvingRequest`]s.
+pub trait Prover {
+    /// Creates a proof for the Action circuits in `request`.
+    fn prove(&mut self, request: &ProvingRequest) -> Result<Proof, Error>;
+}
+
+impl<'a> Prover for &'a ProvingKey {
+    fn prove(&mut self, request: &ProvingRequest) -> Result<Proof, Error> {
+        Ok(Proof::create_from_request(*self, request, OsRng)?)
+    }
+}
+
+impl<'a, P: Prover + ?Sized> Prover for &'a mut P {
+    fn prove(&mut self, request: &ProvingRequest) -> Result<Proof, Error> {
+        (**self).prove(request)
+    }
+}
+
+/// Writes a frame containing `payload`.
+fn write_frame<W: Write>(mut writer: W, payload: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(payload.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Frame is too large"))?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(payload)?;
+    writer.flush()
+}
+
+/// Reads a frame of at most `max_len` bytes, or returns `None` if the stream ended
+/// before the frame began.
+fn read_frame<R: Read>(mut reader: R, max_len: u32) -> Result<Option<Vec<u8>>, Error> {
+    let mut len = [0u8; 4];
+    let mut filled = 0;
+    while filled < len.len() {
+        match reader.read(&mut len[filled..]) {
+            // Only a stream that ends before any of the length prefix is a clean close.
+            Ok(0) if filled == 0 => return Ok(None),
+            Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    let len = u32::from_le_bytes(len);
+    if len > max_len {
+        return Err(Error::FrameTooLarge(len));
+    }
+
+    let mut payload = vec![0; len as usize];
+    reader.read_exact(&mut payload)?;
+    Ok(Some(payload))
+}
+
+/// A client for a prover service.
+#[derive(Debug)]
+pub struct Client<S> {
+    stream: S,
+}
+
+impl<S: Read + Write> Client<S> {
+    /// Constructs a client that communicates with a prover service over `stream`.
+    ///
+    /// For a service that uses its standard input and output, `stream` would combine
+    /// the pipes to and from the service's process.
+    pub fn new(stream: S) -> Self {
+        Client { stream }
+    }
+
+    /// Returns the underlying stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+#[cfg(unix)]
+#[cfg_attr(docsrs, doc(cfg(unix)))]
+impl Client<UnixStream> {
+    /// Connects to a prover service listening on the Unix socket at `path`.
+    pub fn connect<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        UnixStream::connect(path).map(Client::new)
+    }
+}
+
+impl<S: Read + Write> Prover for Client<S> {
+    fn prove(&mut self, request: &ProvingRequest) -> Result<Proof, Error> {
+        let mut payload = vec![];
+        request.write(&mut payload)?;
+        if payload.len() > MAX_REQUEST_SIZE as usize {
+            return Err(Error::FrameTooLarge(
+                u32::try_from(payload.len()).unwrap_or(u32::MAX),
+            ));
+        }
+        write_frame(&mut self.stream, &payload)?;
+
+        let response =
+            read_frame(&mut self.stream, MAX_RESPONSE_SIZE)?.ok_or(Error::InvalidResponse)?;
+        match response.split_first() {
+            Some((&RESPONSE_OK, proof)) => Ok(Proof::new(proof.to_vec())),
+            Some((&RESPONSE_ERROR, msg)) => {
+                Err(Error::Remote(String::from_utf8_lossy(msg).into_owned()))
+            }
+            _ => Err(Error::InvalidResponse),
+        }
+    }
+}
+
+/// Serves proving requests from `stream` until it is closed.
+///
+/// Each request is proven with `prover`, and the proof or error is written back to
+/// `stream`. Requests that cannot be parsed are answered with an error. Returns an error
+/// if a request frame is too large, or if reading or writing fails, in which case the
+/// connection should be dropped.
+pub fn serve<P: Prover, S: Read + Write>(mut prover: P, mut stream: S) -> Result<(), Error> {
+    while let Some(payload) = read_frame(&mut stream, MAX_REQUEST_SIZE)? {
+        let result = ProvingRequest::read(&payload[..])
+            .map_err(Error::from)
+            .and_then(|request| prover.prove(&request));
+
+        let response = match result {
+            Ok(proof) => [&[RESPONSE_OK][..], proof.as_ref()].concat(),
+            Err(e) => [&[RESPONSE_ERROR][..], e.to_string().as_bytes()].concat(),
+        };
+        write_frame(&mut stream, &response)?;
+    }
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::io;
+    use std::os::unix::net::UnixStream;
+    use std::thread;
+
+    use rand::rngs::OsRng;
+
+    use super::{read_frame, serve, Client, Error, MAX_REQUEST_SIZE};
+    use crate::{
+        builder::Builder,
+        bundle::{Authorized, Bundle, Flags},
+        circuit::{ProvingKey, VerifyingKey},
+        constants::MERKLE_DEPTH_ORCHARD,
+        keys::{FullViewingKey, Scope, SpendingKey},
+        tree::EMPTY_ROOTS,
+        value::NoteValue,
+    };
+
+    #[test]
+    fn prover_service() {
+        let mut rng = OsRng;
+
+        let (client, server) = UnixStream::pair().unwrap();
+        let service = thread::spawn(move || serve(&ProvingKey::fake(), server));
+        let mut client = Client::new(client);
+
+        let recipient =
+            FullViewingKey::from(&SpendingKey::random(&mut rng)).address_at(0u32, Scope::External);
+        for _ in 0..2 {
+            let mut builder = Builder::new(
+                Flags::from_parts(true, true),
+                EMPTY_ROOTS[MERKLE_DEPTH_ORCHARD].into(),
+            );
+            builder
+                .add_recipient(None, recipient, NoteValue::from_raw(5000), None)
+                .unwrap();
+            let bundle: Bundle<Authorized, i64> = builder
+                .build(&mut rng)
+                .unwrap()
+                .create_proof_with(&mut client, &VerifyingKey::fake())
+                .unwrap()
+                .prepare(&mut rng, [0; 32])
+                .finalize()
+                .unwrap();
+            assert!(bundle.verify_proof(&VerifyingKey::fake()).is_ok());
+        }
+
+        drop(client);
+        assert!(service.join().unwrap().is_ok());
+    }
+
+    #[test]
+    fn frame_limits() {
+        let mut frame = (MAX_REQUEST_SIZE + 1).to_le_bytes().to_vec();
+        frame.extend_from_slice(&[0; 16]);
+        assert!(matches!(
+            read_frame(&frame[..], MAX_REQUEST_SIZE),
+            Err(Error::FrameTooLarge(_))
+        ));
+
+        // A truncated frame or length prefix is an error, but a closed stream is not.
+        assert!(read_frame(&[4, 0, 0, 0, 1][..], MAX_REQUEST_SIZE).is_err());
+        assert!(matches!(
+            read_frame(&[4, 0][..], MAX_REQUEST_SIZE),
+            Err(Error::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof
+        ));
+        assert!(read_frame(&[][..], MAX_REQUEST_SIZE).unwrap().is_none());
+    }
+}
@@ -0,0 +1,368 @@
+//! `serde` implementations for the public Orchard types.
+//!
+//! Each type is serialized using its canonical byte encoding, which is written as a hex
+//! string in human-readable formats and as a byte string otherwise. Deserialization
+//! rejects non-canonical encodings, in the same way as the corresponding `from_bytes` or
+//! `read` method.
+
+use std::fmt;
+
+use nonempty::NonEmpty;
+use serde::{
+    de::{self, Deserializer, SeqAccess, Visitor},
+    ser::Serializer,
+    Deserialize, Serialize,
+};
+
+use crate::{
+    action::Action,
+    address::Address,
+    bundle::{Authorized, Bundle, Flags},
+    circuit::Proof,
+    keys::FullViewingKey,
+    note::{ExtractedNoteCommitment, Note, Nullifier, TransmittedNoteCiphertext},
+    primitives::redpallas::{self, SpendAuth},
+    tree::Anchor,
+    value::ValueCommitment,
+};
+
+/// A byte string, encoded as hex in human-readable formats.
+struct ByteString(Vec<u8>);
+
+impl Serialize for ByteString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&hex::encode(&self.0))
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+struct ByteStringVisitor;
+
+impl<'de> Visitor<'de> for ByteStringVisitor {
+    type Value = ByteString;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a byte string or a hex string")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        hex::decode(v)
+            .map(ByteString)
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(ByteString(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(ByteString(v))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(b) = seq.next_element()? {
+            bytes.push(b);
+        }
+        Ok(ByteString(bytes))
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(ByteStringVisitor)
+        } else {
+            deserializer.deserialize_byte_buf(ByteStringVisitor)
+        }
+    }
+}
+
+impl ByteString {
+    fn into_array<E: de::Error, const N: usize>(self) -> Result<[u8; N], E> {
+        let len = self.0.len();
+        self.0
+            .try_into()
+            .map_err(|_| E::invalid_length(len, &format!("{} bytes", N).as_str()))
+    }
+}
+
+fn serialize_bytes<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    ByteString(bytes.to_vec()).serialize(serializer)
+}
+
+fn deserialize_array<'de, D: Deserializer<'de>, const N: usize>(
+    deserializer: D,
+) -> Result<[u8; N], D::Error> {
+    ByteString::deserialize(deserializer)?.into_array()
+}
+
+fn non_canonical<E: de::Error>(what: &str) -> E {
+    E::custom(format!(
+        "Attempted to deserialize a non-canonical encoding of {}.",
+        what
+    ))
+}
+
+/// Implements `Serialize` and `Deserialize` for a type with a fixed-length encoding.
+macro_rules! impl_serde_bytes {
+    ($t:ty, $n:expr, $to_bytes:expr, $from_bytes:expr, $what:expr) => {
+        impl Serialize for $t {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serialize_bytes(&$to_bytes(self), serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $t {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let bytes: [u8; $n] = deserialize_array(deserializer)?;
+                Option::from($from_bytes(&bytes)).ok_or_else(|| non_canonical($what))
+            }
+        }
+    };
+}
+
+impl_serde_bytes!(
+    Anchor,
+    32,
+    |a: &Anchor| a.to_bytes(),
+    |b: &[u8; 32]| Anchor::from_bytes(*b),
+    "an anchor"
+);
+impl_serde_bytes!(
+    Nullifier,
+    32,
+    |nf: &Nullifier| nf.to_bytes(),
+    Nullifier::from_bytes,
+    "a nullifier"
+);
+impl_serde_bytes!(
+    ExtractedNoteCommitment,
+    32,
+    |cmx: &ExtractedNoteCommitment| cmx.to_bytes(),
+    ExtractedNoteCommitment::from_bytes,
+    "a note commitment"
+);
+impl_serde_bytes!(
+    ValueCommitment,
+    32,
+    ValueCommitment::to_bytes,
+    ValueCommitment::from_bytes,
+    "a value commitment"
+);
+impl_serde_bytes!(
+    Address,
+    43,
+    Address::to_raw_address_bytes,
+    Address::from_raw_address_bytes,
+    "an address"
+);
+impl_serde_bytes!(
+    FullViewingKey,
+    96,
+    FullViewingKey::to_bytes,
+    FullViewingKey::from_bytes,
+    "a full viewing key"
+);
+
+impl Serialize for Note {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut bytes = vec![];
+        self.write(&mut bytes)
+            .expect("writing to a Vec cannot fail");
+        serialize_bytes(&bytes, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Note {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = ByteString::deserialize(deserializer)?.0;
+        let mut reader = &bytes[..];
+        match Note::read(&mut reader) {
+            Ok(note) if reader.is_empty() => Ok(note),
+            _ => Err(non_canonical("a note")),
+        }
+    }
+}
+
+/// The serialized form of an authorized [`Action`].
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Action")]
+struct ActionRepr {
+    cv_net: ValueCommitment,
+    nullifier: Nullifier,
+    rk: ByteString,
+    cmx: ExtractedNoteCommitment,
+    epk: ByteString,
+    enc_ciphertext: ByteString,
+    out_ciphertext: ByteString,
+    spend_auth_sig: ByteString,
+}
+
+impl From<&Action<redpallas::Signature<SpendAuth>>> for ActionRepr {
+    fn from(action: &Action<redpallas::Signature<SpendAuth>>) -> Self {
+        let encrypted_note = action.encrypted_note();
+        ActionRepr {
+            cv_net: action.cv_net().clone(),
+            nullifier: *action.nullifier(),
+            rk: ByteString(<[u8; 32]>::from(action.rk()).to_vec()),
+            cmx: *action.cmx(),
+            epk: ByteString(encrypted_note.epk_bytes.to_vec()),
+            enc_ciphertext: ByteString(encrypted_note.enc_ciphertext.to_vec()),
+            out_ciphertext: ByteString(encrypted_note.out_ciphertext.to_vec()),
+            spend_auth_sig: ByteString(<[u8; 64]>::from(action.authorization()).to_vec()),
+        }
+    }
+}
+
+impl ActionRepr {
+    fn into_action<E: de::Error>(self) -> Result<Action<redpallas::Signature<SpendAuth>>, E> {
+        let rk = redpallas::VerificationKey::try_from(self.rk.into_array::<E, 32>()?)
+            .map_err(|_| non_canonical::<E>("a randomized validating key"))?;
+        let encrypted_note = TransmittedNoteCiphertext {
+            epk_bytes: self.epk.into_array::<E, 32>()?,
+            enc_ciphertext: self.enc_ciphertext.into_array::<E, 580>()?,
+            out_ciphertext: self.out_ciphertext.into_array::<E, 80>()?,
+        };
+        let spend_auth_sig = self.spend_auth_sig.into_array::<E, 64>()?;
+
+        Ok(Action::from_parts(
+            self.nullifier,
+            rk,
+            self.cmx,
+            encrypted_note,
+            self.cv_net,
+            redpallas::Signature::from(spend_auth_sig),
+        ))
+    }
+}
+
+impl Serialize for Action<redpallas::Signature<SpendAuth>> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ActionRepr::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Action<redpallas::Signature<SpendAuth>> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        ActionRepr::deserialize(deserializer)?.into_action()
+    }
+}
+
+/// The serialized form of an authorized [`Bundle`].
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Bundle")]
+struct BundleRepr<V> {
+    actions: Vec<Action<redpallas::Signature<SpendAuth>>>,
+    flags: u8,
+    value_balance: V,
+    anchor: Anchor,
+    proof: ByteString,
+    binding_sig: ByteString,
+}
+
+impl<V: Serialize + Clone> Serialize for Bundle<Authorized, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BundleRepr {
+            actions: self.actions().iter().cloned().collect(),
+            flags: self.flags().to_byte(),
+            value_balance: self.value_balance().clone(),
+            anchor: *self.anchor(),
+            proof: ByteString(self.authorization().proof().as_ref().to_vec()),
+            binding_sig: ByteString(
+                <[u8; 64]>::from(self.authorization().binding_signature()).to_vec(),
+            ),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, V: Deserialize<'de>> Deserialize<'de> for Bundle<Authorized, V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = BundleRepr::<V>::deserialize(deserializer)?;
+        let actions = NonEmpty::from_vec(repr.actions).ok_or_else(|| {
+            <D::Error as de::Error>::custom("An Orchard bundle must have at least one action.")
+        })?;
+        let flags = Flags::from_byte(repr.flags)
+            .ok_or_else(|| non_canonical::<D::Error>("bundle flags"))?;
+        let binding_sig = repr.binding_sig.into_array::<D::Error, 64>()?;
+
+        Ok(Bundle::from_parts(
+            actions,
+            flags,
+            repr.value_balance,
+            repr.anchor,
+            Authorized::from_parts(Proof::new(repr.proof.0), binding_sig.into()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::{
+        bundle::{testing::arb_bundle, Authorized, Bundle},
+        keys::{testing::arb_spending_key, FullViewingKey, Scope},
+        note::{testing::arb_note, Nullifier},
+        tree::Anchor,
+        value::testing::arb_note_value,
+        Address, Note,
+    };
+
+    #[test]
+    fn hex_in_human_readable_formats() {
+        let nf = Nullifier::from_bytes(&[7; 32]).unwrap();
+        let json = serde_json::to_string(&nf).unwrap();
+        assert_eq!(json, format!("\"{}\"", "07".repeat(32)));
+        assert_eq!(serde_json::from_str::<Nullifier>(&json).unwrap(), nf);
+    }
+
+    #[test]
+    fn rejects_non_canonical() {
+        // The Pallas base field modulus is not a canonical encoding.
+        let p = "01000000ed302d991bf94c09fc98462200000000000000000000000000000040";
+        assert!(serde_json::from_str::<Anchor>(&format!("\"{}\"", p)).is_err());
+        assert!(serde_json::from_str::<Nullifier>(&format!("\"{}\"", p)).is_err());
+
+        // Wrong lengths and invalid hex are rejected.
+        assert!(serde_json::from_str::<Nullifier>(&format!("\"{}\"", "00".repeat(31))).is_err());
+        assert!(serde_json::from_str::<Nullifier>(&format!("\"{}\"", "zz".repeat(32))).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn keys_and_notes_round_trip(
+            sk in arb_spending_key(),
+            note in arb_note_value().prop_flat_map(arb_note),
+        ) {
+            let fvk = FullViewingKey::from(&sk);
+            let json = serde_json::to_string(&fvk).unwrap();
+            assert_eq!(serde_json::from_str::<FullViewingKey>(&json).unwrap(), fvk);
+
+            let addr = fvk.address_at(0u32, Scope::External);
+            let json = serde_json::to_string(&addr).unwrap();
+            assert_eq!(serde_json::from_str::<Address>(&json).unwrap(), addr);
+
+            let json = serde_json::to_string(&note).unwrap();
+            let parsed: Note = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed.commitment().to_bytes(), note.commitment().to_bytes());
+        }
+
+        #[test]
+        fn bundle_round_trip(bundle in arb_bundle(2)) {
+            let bundle = bundle.try_map_value_balance(i64::try_from).unwrap();
+            let json = serde_json::to_string(&bundle).unwrap();
+            let parsed: Bundle<Authorized, i64> = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed.commitment().0, bundle.commitment().0);
+            assert_eq!(
+                parsed.authorizing_commitment().0,
+                bundle.authorizing_commitment().0
+            );
+            assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+        }
+    }
+}
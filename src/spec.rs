@@ -1,5 +1,6 @@
 //! Helper functions defined in the Zcash Protocol Specification.
 
+use core::cmp::Ordering;
 use core::iter;
 use core::ops::Deref;
 
@@ -9,7 +10,8 @@ use group::{Curve, Group};
 use halo2_gadgets::{poseidon::primitives as poseidon, sinsemilla::primitives as sinsemilla};
 use halo2_proofs::arithmetic::{CurveAffine, CurveExt, FieldExt};
 use pasta_curves::pallas;
-use subtle::{ConditionallySelectable, CtOption};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+use zeroize::{DefaultIsZeroes, Zeroize};
 
 use crate::constants::{
     fixed_bases::COMMIT_IVK_PERSONALIZATION, util::gen_const_array,
@@ -35,6 +37,8 @@ impl ConditionallySelectable for NonIdentityPallasPoint {
     }
 }
 
+impl DefaultIsZeroes for NonIdentityPallasPoint {}
+
 impl NonIdentityPallasPoint {
     pub(crate) fn from_bytes(bytes: &[u8; 32]) -> CtOption<Self> {
         pallas::Point::from_bytes(bytes)
@@ -100,6 +104,8 @@ impl Default for NonZeroPallasScalar {
     }
 }
 
+impl DefaultIsZeroes for NonZeroPallasScalar {}
+
 impl From<NonZeroPallasBase> for NonZeroPallasScalar {
     fn from(s: NonZeroPallasBase) -> Self {
         NonZeroPallasScalar::guaranteed(mod_r_p(s.0))
@@ -140,6 +146,87 @@ impl Deref for NonZeroPallasScalar {
     }
 }
 
+/// A secret field element.
+///
+/// Field elements are `Copy`, and so cannot clear themselves when they are dropped.
+/// Types that hold secret field elements store them in this wrapper, and zeroize it when
+/// they are dropped. The wrapper is not `Copy`, so that the secret is not duplicated
+/// implicitly, and it is compared in constant time.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SecretField<F>(ZeroableField<F>);
+
+/// The field element held by a [`SecretField`], which is reset to the default value when
+/// zeroized.
+#[derive(Clone, Copy, Debug, Default)]
+struct ZeroableField<F>(F);
+
+impl<F: Copy + Default> DefaultIsZeroes for ZeroableField<F> {}
+
+impl<F> SecretField<F> {
+    pub(crate) fn new(value: F) -> Self {
+        SecretField(ZeroableField(value))
+    }
+}
+
+impl<F: Copy + Default> Zeroize for SecretField<F> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<F> Deref for SecretField<F> {
+    type Target = F;
+
+    fn deref(&self) -> &F {
+        &self.0 .0
+    }
+}
+
+impl<F: Field> ConstantTimeEq for SecretField<F> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0 .0.ct_eq(&other.0 .0)
+    }
+}
+
+impl<F: Field> PartialEq for SecretField<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl<F: Field> Eq for SecretField<F> {}
+
+impl<F: PrimeField> PartialOrd for SecretField<F> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F: PrimeField> Ord for SecretField<F> {
+    /// Orders field elements by their canonical encodings, as the field types do, but
+    /// without branching on the secret bytes.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.0 .0.to_repr(), other.0 .0.to_repr());
+        let mut less = Choice::from(0);
+        let mut greater = Choice::from(0);
+        // The encodings are little-endian, so the most significant difference is the
+        // last one.
+        for (a, b) in a.as_ref().iter().zip(b.as_ref().iter()).rev() {
+            let (a, b) = (u16::from(*a), u16::from(*b));
+            let undecided = !(less | greater);
+            less |= undecided & Choice::from((a.wrapping_sub(b) >> 8) as u8 & 1);
+            greater |= undecided & Choice::from((b.wrapping_sub(a) >> 8) as u8 & 1);
+        }
+        if less.into() {
+            Ordering::Less
+        } else if greater.into() {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    }
+}
+
 /// $\mathsf{ToBase}^\mathsf{Orchard}(x) := LEOS2IP_{\ell_\mathsf{PRFexpand}}(x) (mod q_P)$
 ///
 /// Defined in [Zcash Protocol Spec § 4.2.3: Orchard Key Components][orchardkeycomponents].
@@ -270,8 +357,9 @@ pub fn i2lebsp<const NUM_BITS: usize>(int: u64) -> [bool; NUM_BITS] {
 
 #[cfg(test)]
 mod tests {
-    use super::{i2lebsp, lebs2ip};
+    use super::{i2lebsp, lebs2ip, SecretField};
 
+    use ff::Field;
     use group::Group;
     use halo2_proofs::arithmetic::CurveExt;
     use pasta_curves::pallas;
@@ -284,6 +372,20 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn secret_field_comparisons() {
+        let mut rng = OsRng;
+        for _ in 0..100 {
+            let a = pallas::Scalar::random(&mut rng);
+            let b = pallas::Scalar::random(&mut rng);
+            for (a, b) in [(a, b), (a, a), (a, a + pallas::Scalar::one())] {
+                let (x, y) = (SecretField::new(a), SecretField::new(b));
+                assert_eq!(x == y, a == b);
+                assert_eq!(x.cmp(&y), a.cmp(&b));
+            }
+        }
+    }
+
     #[test]
     fn lebs2ip_round_trip() {
         let mut rng = OsRng;
@@ -13,6 +13,8 @@ pub(crate) enum PrfExpand {
     OrchardZip32Child,
     OrchardDkOvk,
     OrchardRivkInternal,
+    #[cfg(feature = "zsa")]
+    ZsaIsk,
 }
 
 impl PrfExpand {
@@ -27,6 +29,8 @@ impl PrfExpand {
             Self::OrchardZip32Child => 0x81,
             Self::OrchardDkOvk => 0x82,
             Self::OrchardRivkInternal => 0x83,
+            #[cfg(feature = "zsa")]
+            Self::ZsaIsk => 0x0a,
         }
     }
 
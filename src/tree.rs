@@ -1,6 +1,7 @@
 //! Types related to Orchard note commitment trees and anchors.
 
 use core::iter;
+use std::collections::VecDeque;
 
 use crate::{
     constants::{
@@ -75,9 +76,99 @@ impl Anchor {
     }
 }
 
+/// A set of anchors that bundles are allowed to use, recorded by block height.
+///
+/// Consensus requires the anchor of a bundle that enables spends to be the root of the
+/// note commitment tree at the end of some prior block. Implementations may choose to
+/// only remember the anchors of recent blocks.
+pub trait AnchorSet {
+    /// Records `anchor` as the root of the note commitment tree at the end of the block
+    /// at `height`.
+    ///
+    /// Any anchors recorded at `height` or greater are replaced.
+    fn record(&mut self, height: u32, anchor: Anchor);
+
+    /// Returns whether `anchor` is in this set.
+    fn contains(&self, anchor: &Anchor) -> bool;
+
+    /// Removes the anchors of every block after `height`, so that blocks can be applied
+    /// again from `height + 1` after a chain reorganization.
+    fn rewind(&mut self, height: u32);
+}
+
+/// An [`AnchorSet`] that holds the anchors of the most recent blocks, in a ring buffer.
+#[derive(Clone, Debug)]
+pub struct AnchorWindow {
+    capacity: usize,
+    anchors: VecDeque<(u32, Anchor)>,
+}
+
+impl AnchorWindow {
+    /// Constructs an empty window that will hold the anchors of at most `capacity`
+    /// blocks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "AnchorWindow capacity must be non-zero");
+        AnchorWindow {
+            capacity,
+            anchors: VecDeque::new(),
+        }
+    }
+
+    /// Returns the number of anchors in this window.
+    pub fn len(&self) -> usize {
+        self.anchors.len()
+    }
+
+    /// Returns whether this window is empty.
+    pub fn is_empty(&self) -> bool {
+        self.anchors.is_empty()
+    }
+
+    /// Returns the most recently recorded height and anchor.
+    pub fn latest(&self) -> Option<(u32, Anchor)> {
+        self.anchors.back().copied()
+    }
+
+    /// Returns the anchor recorded at `height`, if it is in this window.
+    pub fn get(&self, height: u32) -> Option<Anchor> {
+        self.anchors
+            .iter()
+            .find(|(h, _)| *h == height)
+            .map(|(_, anchor)| *anchor)
+    }
+}
+
+impl AnchorSet for AnchorWindow {
+    fn record(&mut self, height: u32, anchor: Anchor) {
+        if let Some(prev) = height.checked_sub(1) {
+            self.rewind(prev);
+        } else {
+            self.anchors.clear();
+        }
+        if self.anchors.len() == self.capacity {
+            self.anchors.pop_front();
+        }
+        self.anchors.push_back((height, anchor));
+    }
+
+    fn contains(&self, anchor: &Anchor) -> bool {
+        self.anchors.iter().any(|(_, a)| a == anchor)
+    }
+
+    fn rewind(&mut self, height: u32) {
+        while matches!(self.anchors.back(), Some((h, _)) if *h > height) {
+            self.anchors.pop_back();
+        }
+    }
+}
+
 /// The Merkle path from a leaf of the note commitment tree
 /// to its anchor.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct MerklePath {
     position: u32,
     auth_path: [MerkleHashOrchard; MERKLE_DEPTH_ORCHARD],
@@ -254,7 +345,7 @@ pub mod testing {
     };
 
     #[cfg(test)]
-    use crate::tree::{MerkleHashOrchard, EMPTY_ROOTS};
+    use crate::tree::{Anchor, AnchorSet, AnchorWindow, MerkleHashOrchard, EMPTY_ROOTS};
     #[cfg(test)]
     use group::ff::PrimeField;
     #[cfg(test)]
@@ -364,4 +455,31 @@ pub mod testing {
         }
         assert_eq!(frontier.root().0, pallas::Base::from_repr(anchor).unwrap());
     }
+
+    #[test]
+    fn anchor_window() {
+        let anchor = |i: u64| Anchor::from(pallas::Base::from(i));
+
+        let mut window = AnchorWindow::new(3);
+        assert!(window.is_empty());
+        for height in 0..5 {
+            window.record(height, anchor(height.into()));
+        }
+
+        // Only the most recent anchors are kept.
+        assert_eq!(window.len(), 3);
+        assert!(!window.contains(&anchor(1)));
+        assert!(window.contains(&anchor(2)));
+        assert_eq!(window.latest(), Some((4, anchor(4))));
+
+        // A reorg replaces the anchors of the blocks that were rolled back.
+        window.rewind(2);
+        assert!(!window.contains(&anchor(4)));
+        assert_eq!(window.latest(), Some((2, anchor(2))));
+        window.record(3, anchor(30));
+        window.record(3, anchor(31));
+        assert!(!window.contains(&anchor(30)));
+        assert_eq!(window.get(3), Some(anchor(31)));
+        assert_eq!(window.get(1), None);
+    }
 }
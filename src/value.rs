@@ -49,14 +49,20 @@ use pasta_curves::{
 };
 use rand::RngCore;
 use subtle::CtOption;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use crate::{
+    bundle::{Authorization, Bundle},
     constants::fixed_bases::{
         VALUE_COMMITMENT_PERSONALIZATION, VALUE_COMMITMENT_R_BYTES, VALUE_COMMITMENT_V_BYTES,
     },
     primitives::redpallas::{self, Binding},
+    spec::SecretField,
 };
 
+#[cfg(feature = "zsa")]
+use crate::note::AssetBase;
+
 /// Maximum note value.
 pub const MAX_NOTE_VALUE: u64 = u64::MAX;
 
@@ -209,12 +215,36 @@ impl TryFrom<ValueSum> for i64 {
 }
 
 /// The blinding factor for a [`ValueCommitment`].
+///
+/// The trapdoor is zeroized when it is dropped.
 #[derive(Clone, Debug)]
-pub struct ValueCommitTrapdoor(pallas::Scalar);
+pub struct ValueCommitTrapdoor(SecretField<pallas::Scalar>);
+
+impl Zeroize for ValueCommitTrapdoor {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for ValueCommitTrapdoor {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for ValueCommitTrapdoor {}
 
 impl ValueCommitTrapdoor {
     pub(crate) fn inner(&self) -> pallas::Scalar {
-        self.0
+        *self.0
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8; 32]) -> CtOption<Self> {
+        pallas::Scalar::from_repr(*bytes).map(|rcv| ValueCommitTrapdoor(SecretField::new(rcv)))
+    }
+
+    pub(crate) fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_repr()
     }
 }
 
@@ -222,7 +252,7 @@ impl Add<&ValueCommitTrapdoor> for ValueCommitTrapdoor {
     type Output = ValueCommitTrapdoor;
 
     fn add(self, rhs: &Self) -> Self::Output {
-        ValueCommitTrapdoor(self.0 + rhs.0)
+        ValueCommitTrapdoor(SecretField::new(*self.0 + *rhs.0))
     }
 }
 
@@ -235,12 +265,12 @@ impl<'a> Sum<&'a ValueCommitTrapdoor> for ValueCommitTrapdoor {
 impl ValueCommitTrapdoor {
     /// Generates a new value commitment trapdoor.
     pub(crate) fn random(rng: impl RngCore) -> Self {
-        ValueCommitTrapdoor(pallas::Scalar::random(rng))
+        ValueCommitTrapdoor(SecretField::new(pallas::Scalar::random(rng)))
     }
 
     /// Returns the zero trapdoor, which provides no blinding.
     pub(crate) fn zero() -> Self {
-        ValueCommitTrapdoor(pallas::Scalar::zero())
+        ValueCommitTrapdoor(SecretField::new(pallas::Scalar::zero()))
     }
 
     pub(crate) fn into_bsk(self) -> redpallas::SigningKey<Binding> {
@@ -304,7 +334,34 @@ impl ValueCommitment {
             pallas::Scalar::from(abs_value)
         };
 
-        ValueCommitment(V * value + R * rcv.0)
+        ValueCommitment(V * value + R * rcv.inner())
+    }
+
+    /// $ValueCommit^{OrchardZSA}$, a value commitment to a value of the given asset.
+    ///
+    /// For the native asset this is identical to $ValueCommit^Orchard$.
+    ///
+    /// Defined in [ZIP 226: Transfer and Burn of Zcash Shielded Assets][valuecommit].
+    ///
+    /// [valuecommit]: https://zips.z.cash/zip-0226#value-commitment-correctness
+    #[cfg(feature = "zsa")]
+    #[allow(non_snake_case)]
+    pub(crate) fn derive_with_asset(
+        value: ValueSum,
+        rcv: ValueCommitTrapdoor,
+        asset: AssetBase,
+    ) -> Self {
+        let hasher = pallas::Point::hash_to_curve(VALUE_COMMITMENT_PERSONALIZATION);
+        let R = hasher(&VALUE_COMMITMENT_R_BYTES);
+        let abs_value = u64::try_from(value.0.abs()).expect("value must be in valid range");
+
+        let value = if value.0.is_negative() {
+            -pallas::Scalar::from(abs_value)
+        } else {
+            pallas::Scalar::from(abs_value)
+        };
+
+        ValueCommitment(asset.cv_base() * value + R * rcv.inner())
     }
 
     pub(crate) fn into_bvk(self) -> redpallas::VerificationKey<Binding> {
@@ -341,6 +398,90 @@ impl ValueCommitment {
     }
 }
 
+/// An error that can occur when applying value balances to a [`ValuePool`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TurnstileError {
+    /// The sum of the value balances, or the resulting pool balance, is out of range.
+    Overflow,
+    /// The value balances would withdraw more value from the pool than it contains.
+    NegativePool {
+        /// The amount by which the pool balance would be negative.
+        deficit: u64,
+    },
+}
+
+impl fmt::Display for TurnstileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TurnstileError::Overflow => write!(f, "{}", OverflowError),
+            TurnstileError::NegativePool { deficit } => write!(
+                f,
+                "Orchard pool balance would become negative by {} zatoshis",
+                deficit
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TurnstileError {}
+
+/// The total value held in the Orchard shielded pool.
+///
+/// This implements the turnstile defined in [ZIP 209: Prohibit Negative Shielded Chain
+/// Value Pool Balances][zip209]: each bundle's `valueBalanceOrchard` is withdrawn from the
+/// pool, and a block is invalid if it would make the pool balance negative.
+///
+/// [zip209]: https://zips.z.cash/zip-0209
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ValuePool(u64);
+
+impl ValuePool {
+    /// Returns a pool holding `balance`.
+    pub fn from_balance(balance: NoteValue) -> Self {
+        ValuePool(balance.inner())
+    }
+
+    /// Returns the value held in the pool.
+    pub fn balance(&self) -> NoteValue {
+        NoteValue(self.0)
+    }
+
+    /// Applies the value balances of a block of transactions to the pool.
+    ///
+    /// The pool balance is only required to be non-negative at the end of the block. If
+    /// an error is returned, the pool is unchanged.
+    pub fn apply_value_balances(
+        &mut self,
+        value_balances: impl IntoIterator<Item = i64>,
+    ) -> Result<NoteValue, TurnstileError> {
+        let withdrawn = value_balances
+            .into_iter()
+            .map(ValueSum::from_raw)
+            .sum::<Result<ValueSum, OverflowError>>()
+            .map_err(|_| TurnstileError::Overflow)?;
+
+        let balance = i128::from(self.0) - withdrawn.0;
+        if balance.is_negative() {
+            Err(TurnstileError::NegativePool {
+                deficit: u64::try_from(-balance).map_err(|_| TurnstileError::Overflow)?,
+            })
+        } else {
+            self.0 = u64::try_from(balance).map_err(|_| TurnstileError::Overflow)?;
+            Ok(self.balance())
+        }
+    }
+
+    /// Applies the value balances of a block of bundles to the pool.
+    ///
+    /// See [`ValuePool::apply_value_balances`].
+    pub fn apply_block<'a, T: Authorization + 'a, V: Copy + Into<i64> + 'a>(
+        &mut self,
+        block: impl IntoIterator<Item = &'a Bundle<T, V>>,
+    ) -> Result<NoteValue, TurnstileError> {
+        self.apply_value_balances(block.into_iter().map(|b| (*b.value_balance()).into()))
+    }
+}
+
 /// Generators for property testing.
 #[cfg(any(test, feature = "test-dependencies"))]
 #[cfg_attr(docsrs, doc(cfg(feature = "test-dependencies")))]
@@ -377,7 +518,7 @@ pub mod testing {
     prop_compose! {
         /// Generate an arbitrary ValueCommitTrapdoor
         pub fn arb_trapdoor()(rcv in arb_scalar()) -> ValueCommitTrapdoor {
-            ValueCommitTrapdoor(rcv)
+            ValueCommitTrapdoor(SecretField::new(rcv))
         }
     }
 
@@ -408,13 +549,27 @@ pub mod testing {
 #[cfg(test)]
 mod tests {
     use proptest::prelude::*;
+    use rand::rngs::OsRng;
+    use zeroize::Zeroize;
 
     use super::{
         testing::{arb_note_value_bounded, arb_trapdoor, arb_value_sum_bounded},
-        OverflowError, ValueCommitTrapdoor, ValueCommitment, ValueSum, MAX_NOTE_VALUE,
+        NoteValue, OverflowError, TurnstileError, ValueCommitTrapdoor, ValueCommitment, ValuePool,
+        ValueSum, MAX_NOTE_VALUE,
     };
     use crate::primitives::redpallas;
 
+    #[test]
+    fn trapdoors_are_zeroized() {
+        let mut rcv = ValueCommitTrapdoor::random(OsRng);
+        let mut bsk = rcv.clone().into_bsk();
+
+        rcv.zeroize();
+        assert_eq!(rcv.to_bytes(), [0; 32]);
+        bsk.zeroize();
+        assert_eq!(<[u8; 32]>::from(&bsk), [0; 32]);
+    }
+
     proptest! {
         #[test]
         fn bsk_consistent_with_bvk(
@@ -446,4 +601,48 @@ mod tests {
             assert_eq!(redpallas::VerificationKey::from(&bsk), bvk);
         }
     }
+
+    #[cfg(feature = "zsa")]
+    proptest! {
+        #[test]
+        fn native_asset_value_commitment(
+            value in arb_value_sum_bounded(super::NoteValue(MAX_NOTE_VALUE)),
+            rcv in arb_trapdoor(),
+        ) {
+            use crate::note::AssetBase;
+
+            assert_eq!(
+                ValueCommitment::derive(value, rcv.clone()).to_bytes(),
+                ValueCommitment::derive_with_asset(value, rcv, AssetBase::native()).to_bytes(),
+            );
+        }
+    }
+
+    #[test]
+    fn value_pool_turnstile() {
+        let mut pool = ValuePool::default();
+
+        // Shielding adds to the pool, and the balance only needs to be non-negative at
+        // the end of the block.
+        assert_eq!(
+            pool.apply_value_balances(vec![3000, -5000])
+                .unwrap()
+                .inner(),
+            2000
+        );
+        assert_eq!(
+            pool.apply_value_balances(vec![2001]),
+            Err(TurnstileError::NegativePool { deficit: 1 })
+        );
+        assert_eq!(pool.balance().inner(), 2000);
+        assert_eq!(pool.apply_value_balances(vec![2000]).unwrap().inner(), 0);
+
+        // The pool cannot hold more than a note value.
+        let mut pool = ValuePool::from_balance(NoteValue::from_raw(u64::MAX));
+        assert_eq!(
+            pool.apply_value_balances(vec![-1]),
+            Err(TurnstileError::Overflow)
+        );
+        assert_eq!(pool.balance().inner(), u64::MAX);
+    }
 }
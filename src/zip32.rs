@@ -4,6 +4,7 @@ use core::fmt;
 
 use blake2b_simd::Params as Blake2bParams;
 use subtle::{Choice, ConstantTimeEq};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use crate::{
     keys::{FullViewingKey, SpendingKey},
@@ -12,6 +13,8 @@ use crate::{
 
 const ZIP32_ORCHARD_PERSONALIZATION: &[u8; 16] = b"ZcashIP32Orchard";
 const ZIP32_ORCHARD_FVFP_PERSONALIZATION: &[u8; 16] = b"ZcashOrchardFVFP";
+#[cfg(feature = "zsa")]
+const ZIP32_ORCHARD_PERSONALIZATION_FOR_ISSUANCE: &[u8; 16] = b"ZIP32ZSAIssue_V1";
 
 /// Errors produced in derivation of extended spending keys
 #[derive(Debug, PartialEq, Eq)]
@@ -30,6 +33,37 @@ impl fmt::Display for Error {
 
 //impl std::error::Error for Error {}
 
+/// Errors produced in derivation of a spending key from a BIP 39 mnemonic phrase.
+#[cfg(feature = "bip39")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bip39")))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum MnemonicError {
+    /// The phrase is not a valid BIP 39 mnemonic in the English wordlist.
+    InvalidMnemonic(bip39::Error),
+    /// The seed derived from the mnemonic did not produce a valid spending key.
+    Derivation(Error),
+}
+
+#[cfg(feature = "bip39")]
+impl fmt::Display for MnemonicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MnemonicError::InvalidMnemonic(e) => write!(f, "Invalid mnemonic: {}", e),
+            MnemonicError::Derivation(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "bip39")]
+impl std::error::Error for MnemonicError {}
+
+#[cfg(feature = "bip39")]
+impl From<Error> for MnemonicError {
+    fn from(e: Error) -> Self {
+        MnemonicError::Derivation(e)
+    }
+}
+
 /// An Orchard full viewing key fingerprint
 struct FvkFingerprint([u8; 32]);
 
@@ -82,9 +116,25 @@ impl TryFrom<u32> for ChildIndex {
 }
 
 /// The chain code forming the second half of an Orchard extended key.
-#[derive(Debug, Copy, Clone, PartialEq)]
+///
+/// The chain code is zeroized when it is dropped.
+#[derive(Debug, Clone, PartialEq)]
 struct ChainCode([u8; 32]);
 
+impl Zeroize for ChainCode {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for ChainCode {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for ChainCode {}
+
 /// An Orchard extended spending key.
 ///
 /// Defined in [ZIP32: Orchard extended keys][orchardextendedkeys].
@@ -118,7 +168,26 @@ impl ExtendedSpendingKey {
     ///
     /// Panics if seed results in invalid spending key.
     pub fn from_path(seed: &[u8], path: &[ChildIndex]) -> Result<Self, Error> {
-        let mut xsk = Self::master(seed)?;
+        Self::from_path_with_personalization(seed, ZIP32_ORCHARD_PERSONALIZATION, path)
+    }
+
+    /// Returns the key at the given path in the hierarchy of issuance keys, which uses
+    /// Orchard child key derivation from a separately personalized master key.
+    ///
+    /// Defined in [ZIP 227: Issuance key derivation][issuancekeyderivation].
+    ///
+    /// [issuancekeyderivation]: https://zips.z.cash/zip-0227#issuance-key-derivation
+    #[cfg(feature = "zsa")]
+    pub(crate) fn issuance_from_path(seed: &[u8], path: &[ChildIndex]) -> Result<Self, Error> {
+        Self::from_path_with_personalization(seed, ZIP32_ORCHARD_PERSONALIZATION_FOR_ISSUANCE, path)
+    }
+
+    fn from_path_with_personalization(
+        seed: &[u8],
+        personalization: &[u8; 16],
+        path: &[ChildIndex],
+    ) -> Result<Self, Error> {
+        let mut xsk = Self::master_with_personalization(seed, personalization)?;
         for i in path {
             xsk = xsk.derive_child(*i)?;
         }
@@ -135,26 +204,31 @@ impl ExtendedSpendingKey {
     ///
     /// Panics if the seed is shorter than 32 bytes or longer than 252 bytes.
     fn master(seed: &[u8]) -> Result<Self, Error> {
+        Self::master_with_personalization(seed, ZIP32_ORCHARD_PERSONALIZATION)
+    }
+
+    fn master_with_personalization(seed: &[u8], personalization: &[u8; 16]) -> Result<Self, Error> {
         assert!(seed.len() >= 32 && seed.len() <= 252);
-        // I := BLAKE2b-512("ZcashIP32Orchard", seed)
-        let I: [u8; 64] = {
+        // I := BLAKE2b-512(personalization, seed)
+        let mut I: [u8; 64] = {
             let mut I = Blake2bParams::new()
                 .hash_length(64)
-                .personal(ZIP32_ORCHARD_PERSONALIZATION)
+                .personal(personalization)
                 .to_state();
             I.update(seed);
             I.finalize().as_bytes().try_into().unwrap()
         };
         // I_L is used as the master spending key sk_m.
         let sk_m = SpendingKey::from_bytes(I[..32].try_into().unwrap());
+        // I_R is used as the master chain code c_m.
+        let c_m = ChainCode(I[32..].try_into().unwrap());
+        I.zeroize();
+
         if sk_m.is_none().into() {
             return Err(Error::InvalidSpendingKey);
         }
         let sk_m = sk_m.unwrap();
 
-        // I_R is used as the master chain code c_m.
-        let c_m = ChainCode(I[32..].try_into().unwrap());
-
         // For the master extended spending key, depth is 0, parent_fvk_tag is 4 zero bytes, and i is 0.
         Ok(Self {
             depth: 0,
@@ -174,21 +248,22 @@ impl ExtendedSpendingKey {
     /// Discards index if it results in an invalid sk
     fn derive_child(&self, index: ChildIndex) -> Result<Self, Error> {
         // I := PRF^Expand(c_par, [0x81] || sk_par || I2LEOSP(i))
-        let I: [u8; 64] = PrfExpand::OrchardZip32Child.with_ad_slices(
+        let mut I: [u8; 64] = PrfExpand::OrchardZip32Child.with_ad_slices(
             &self.chain_code.0,
             &[self.sk.to_bytes(), &index.0.to_le_bytes()],
         );
 
         // I_L is used as the child spending key sk_i.
         let sk_i = SpendingKey::from_bytes(I[..32].try_into().unwrap());
+        // I_R is used as the child chain code c_i.
+        let c_i = ChainCode(I[32..].try_into().unwrap());
+        I.zeroize();
+
         if sk_i.is_none().into() {
             return Err(Error::InvalidSpendingKey);
         }
         let sk_i = sk_i.unwrap();
 
-        // I_R is used as the child chain code c_i.
-        let c_i = ChainCode(I[32..].try_into().unwrap());
-
         let fvk: FullViewingKey = self.into();
 
         Ok(Self {
@@ -202,7 +277,7 @@ impl ExtendedSpendingKey {
 
     /// Returns sk of this ExtendedSpendingKey.
     pub fn sk(&self) -> SpendingKey {
-        self.sk
+        self.sk.clone()
     }
 }
 